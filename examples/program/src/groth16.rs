@@ -1,17 +1,18 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use snark_bn254_verifier::Groth16Verifier;
+use snark_bn254_verifier::{Groth16Verifier, Sp1Circuit};
 use substrate_bn::Fr;
 
-const GROTH16_VK_BYTES: &[u8] = include_bytes!("../../../../.sp1/circuits/v2.0.0/groth16_vk.bin");
-
 pub fn main() {
     let proof = sp1_zkvm::io::read_vec();
     let vkey_hash = sp1_zkvm::io::read_vec();
     let committed_values_digest = sp1_zkvm::io::read_vec();
 
-    let vk = GROTH16_VK_BYTES;
+    let vk = Sp1Circuit::by_version("v2.0.0")
+        .expect("SP1 v2.0.0 circuits are not embedded")
+        .groth16_vk()
+        .expect("Invalid embedded Groth16 verifying key");
     let vkey_hash = Fr::from_slice(&vkey_hash).expect("Unable to read vkey_hash");
     let committed_values_digest =
         Fr::from_slice(&committed_values_digest).expect("Unable to read committed_values_digest");
@@ -21,11 +22,11 @@ pub fn main() {
     println!("cycle-tracker-end: verify");
 
     match result {
-        Ok(true) => {
+        Ok(()) => {
             println!("Proof is valid");
         }
-        Ok(false) | Err(_) => {
-            println!("Proof is invalid");
+        Err(e) => {
+            println!("Proof is invalid: {e}");
             panic!();
         }
     }
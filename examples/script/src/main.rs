@@ -171,14 +171,10 @@ mod tests {
 
     use super::*;
 
-    use snark_bn254_verifier::{Groth16Verifier, PlonkVerifier};
+    use snark_bn254_verifier::{Groth16Verifier, PlonkVerifier, Sp1Circuit};
     use strum::IntoEnumIterator;
     use substrate_bn::Fr;
 
-    const PLONK_VK_BYTES: &[u8] = include_bytes!("../../../../.sp1/circuits/v2.0.0/plonk_vk.bin");
-    const GROTH16_VK_BYTES: &[u8] =
-        include_bytes!("../../../../.sp1/circuits/v2.0.0/groth16_vk.bin");
-
     #[test]
     fn test_programs() {
         fn verify_proof(proof_file: &str, vk: &[u8], proof_mode: ProofMode) {
@@ -212,7 +208,7 @@ mod tests {
             let committed_values_digest = Fr::from_slice(&committed_values_digest)
                 .expect("Unable to read committed_values_digest");
 
-            let is_valid = match proof_mode {
+            match proof_mode {
                 ProofMode::Groth16 => {
                     Groth16Verifier::verify(&raw_proof, &vk, &[vkey_hash, committed_values_digest])
                         .expect("Groth16 proof is invalid")
@@ -222,17 +218,22 @@ mod tests {
                         .expect("Plonk proof is invalid")
                 }
                 _ => panic!("Invalid proof mode. Use 'groth16' or 'plonk'."),
-            };
-
-            if !is_valid {
-                panic!("{:?} proof is invalid", proof_mode);
             }
         }
 
+        let circuit =
+            Sp1Circuit::by_version("v2.0.0").expect("SP1 v2.0.0 circuits are not embedded");
+        let plonk_vk = circuit
+            .plonk_vk()
+            .expect("Invalid embedded Plonk verifying key");
+        let groth16_vk = circuit
+            .groth16_vk()
+            .expect("Invalid embedded Groth16 verifying key");
+
         Elf::iter().for_each(|program| {
             // Verify Plonk proof
             let proof_file = format!("../binaries/{}_{}_proof.bin", program.to_string(), "plonk");
-            verify_proof(&proof_file, PLONK_VK_BYTES, ProofMode::Plonk);
+            verify_proof(&proof_file, plonk_vk, ProofMode::Plonk);
 
             // Verify Groth16 proof
             let proof_file = format!(
@@ -240,7 +241,7 @@ mod tests {
                 program.to_string(),
                 "groth16"
             );
-            verify_proof(&proof_file, GROTH16_VK_BYTES, ProofMode::Groth16);
+            verify_proof(&proof_file, groth16_vk, ProofMode::Groth16);
         });
     }
 }
@@ -1,7 +1,17 @@
+use crate::error::DecodeError;
+
 pub(crate) const GAMMA: &str = "gamma";
 pub(crate) const BETA: &str = "beta";
 pub(crate) const ALPHA: &str = "alpha";
 pub(crate) const ZETA: &str = "zeta";
+pub(crate) const PLONK_CHALLENGES: [&str; 4] = [GAMMA, BETA, ALPHA, ZETA];
+
+pub(crate) const KZG_BATCH_CHALLENGE: &str = "KZG-batch";
+pub(crate) const GROTH16_BATCH_CHALLENGE: &str = "G16-batch";
+
+pub(crate) const PLONK_COMMITMENT_DST: &[u8] = b"BSB22-Plonk";
+pub(crate) const GROTH16_COMMITMENT_DST: &[u8] = b"bsb22-commitment";
+pub(crate) const GROTH16_COMMITMENT_CHALLENGE_DST: &[u8] = b"G16-BSB22";
 
 pub const MASK: u8 = 0b11 << 6;
 pub const COMPRESSED_POSTIVE: u8 = 0b10 << 6;
@@ -15,13 +25,15 @@ pub enum CompressedPointFlag {
     Infinity = COMPRESSED_INFINITY as isize,
 }
 
-impl From<u8> for CompressedPointFlag {
-    fn from(val: u8) -> Self {
+impl TryFrom<u8> for CompressedPointFlag {
+    type Error = DecodeError;
+
+    fn try_from(val: u8) -> Result<Self, Self::Error> {
         match val {
-            COMPRESSED_POSTIVE => CompressedPointFlag::Positive,
-            COMPRESSED_NEGATIVE => CompressedPointFlag::Negative,
-            COMPRESSED_INFINITY => CompressedPointFlag::Infinity,
-            _ => panic!("Invalid compressed point flag"),
+            COMPRESSED_POSTIVE => Ok(CompressedPointFlag::Positive),
+            COMPRESSED_NEGATIVE => Ok(CompressedPointFlag::Negative),
+            COMPRESSED_INFINITY => Ok(CompressedPointFlag::Infinity),
+            _ => Err(DecodeError::UnexpectedFlag),
         }
     }
 }
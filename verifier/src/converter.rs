@@ -1,13 +1,48 @@
+use alloc::vec::Vec;
 use core::cmp::Ordering;
 
 use bn::{AffineG1, AffineG2, Fq, Fq2};
 
 use crate::{
     constants::{CompressedPointFlag, MASK},
-    error::Error,
+    error::{DecodeError, Error, VkError},
 };
 
-pub fn is_zeroed(first_byte: u8, buf: &[u8]) -> Result<bool, Error> {
+/// The encoding of the curve points in a gnark serialized proof or verifying key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointEncoding {
+    /// Compressed points, as written by gnark's `WriteTo`.
+    Compressed,
+    /// Uncompressed points, as written by gnark's `WriteRawTo`.
+    Uncompressed,
+}
+
+impl PointEncoding {
+    /// Detects the encoding from the first serialized point. Compressed points always carry a
+    /// flag in the two most significant bits, which are unused in uncompressed points.
+    pub(crate) fn detect(buf: &[u8]) -> Self {
+        match buf.first() {
+            Some(first_byte) if first_byte & MASK != 0 => PointEncoding::Compressed,
+            _ => PointEncoding::Uncompressed,
+        }
+    }
+
+    pub(crate) fn g1_size(self) -> usize {
+        match self {
+            PointEncoding::Compressed => 32,
+            PointEncoding::Uncompressed => 64,
+        }
+    }
+
+    pub(crate) fn g2_size(self) -> usize {
+        match self {
+            PointEncoding::Compressed => 64,
+            PointEncoding::Uncompressed => 128,
+        }
+    }
+}
+
+pub fn is_zeroed(first_byte: u8, buf: &[u8]) -> Result<bool, DecodeError> {
     if first_byte != 0 {
         return Ok(false);
     }
@@ -20,15 +55,15 @@ pub fn is_zeroed(first_byte: u8, buf: &[u8]) -> Result<bool, Error> {
     Ok(true)
 }
 
-pub(crate) fn deserialize_with_flags(buf: &[u8]) -> Result<(Fq, CompressedPointFlag), Error> {
+pub(crate) fn deserialize_with_flags(buf: &[u8]) -> Result<(Fq, CompressedPointFlag), DecodeError> {
     if buf.len() != 32 {
-        return Err(Error::InvalidXLength);
+        return Err(DecodeError::InvalidLength);
     };
 
     let m_data = buf[0] & MASK;
     if m_data == CompressedPointFlag::Infinity.into() {
-        if !is_zeroed(buf[0] & !MASK, &buf[1..32]).map_err(|_| Error::InvalidPoint)? {
-            return Err(Error::InvalidPoint);
+        if !is_zeroed(buf[0] & !MASK, &buf[1..32]).map_err(|_| DecodeError::InvalidPoint)? {
+            return Err(DecodeError::InvalidPoint);
         }
         Ok((Fq::zero(), CompressedPointFlag::Infinity))
     } else {
@@ -36,16 +71,19 @@ pub(crate) fn deserialize_with_flags(buf: &[u8]) -> Result<(Fq, CompressedPointF
         x_bytes.copy_from_slice(buf);
         x_bytes[0] &= !MASK;
 
-        let x = Fq::from_be_bytes_mod_order(&x_bytes).expect("Failed to convert x bytes to Fq");
+        // x must be reduced, so that every point has a single encoding
+        let x = Fq::from_slice(&x_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
 
-        Ok((x, m_data.into()))
+        Ok((x, m_data.try_into()?))
     }
 }
 
-#[allow(dead_code)]
-pub(crate) fn compressed_x_to_g1_point(buf: &[u8]) -> Result<AffineG1, Error> {
+pub(crate) fn compressed_x_to_g1_point(buf: &[u8]) -> Result<AffineG1, DecodeError> {
     let (x, m_data) = deserialize_with_flags(buf)?;
-    let (y, neg_y) = AffineG1::get_ys_from_x_unchecked(x).ok_or(Error::InvalidPoint)?;
+    if m_data == CompressedPointFlag::Infinity {
+        return Ok(AffineG1::default());
+    }
+    let (y, neg_y) = AffineG1::get_ys_from_x_unchecked(x).ok_or(DecodeError::InvalidPoint)?;
 
     let mut final_y = y;
     if y.cmp(&neg_y) == Ordering::Greater {
@@ -56,12 +94,15 @@ pub(crate) fn compressed_x_to_g1_point(buf: &[u8]) -> Result<AffineG1, Error> {
         final_y = -y;
     }
 
-    AffineG1::new(x, final_y).map_err(Error::Group)
+    AffineG1::new(x, final_y).map_err(|_| DecodeError::InvalidPoint)
 }
 
-pub(crate) fn unchecked_compressed_x_to_g1_point(buf: &[u8]) -> Result<AffineG1, Error> {
+pub(crate) fn unchecked_compressed_x_to_g1_point(buf: &[u8]) -> Result<AffineG1, DecodeError> {
     let (x, m_data) = deserialize_with_flags(buf)?;
-    let (y, neg_y) = AffineG1::get_ys_from_x_unchecked(x).ok_or(Error::InvalidPoint)?;
+    if m_data == CompressedPointFlag::Infinity {
+        return Ok(AffineG1::default());
+    }
+    let (y, neg_y) = AffineG1::get_ys_from_x_unchecked(x).ok_or(DecodeError::InvalidPoint)?;
 
     let mut final_y = y;
     if y.cmp(&neg_y) == Ordering::Greater {
@@ -75,79 +116,588 @@ pub(crate) fn unchecked_compressed_x_to_g1_point(buf: &[u8]) -> Result<AffineG1,
     Ok(AffineG1::new_unchecked(x, final_y))
 }
 
-pub(crate) fn uncompressed_bytes_to_g1_point(buf: &[u8]) -> Result<AffineG1, Error> {
+pub(crate) fn uncompressed_bytes_to_g1_point(buf: &[u8]) -> Result<AffineG1, DecodeError> {
     if buf.len() != 64 {
-        return Err(Error::InvalidXLength);
+        return Err(DecodeError::InvalidLength);
     };
 
+    // The flag bits are unused in the raw encoding, where gnark writes the point at infinity
+    // as 64 zero bytes
+    if buf[0] & MASK != 0 {
+        return Err(DecodeError::UnexpectedFlag);
+    }
+    if is_zeroed(buf[0], &buf[1..])? {
+        return Ok(AffineG1::default());
+    }
+
     let (x_bytes, y_bytes) = buf.split_at(32);
 
-    let x = Fq::from_slice(x_bytes).map_err(Error::Field)?;
-    let y = Fq::from_slice(y_bytes).map_err(Error::Field)?;
-    AffineG1::new(x, y).map_err(Error::Group)
+    let x = Fq::from_slice(x_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let y = Fq::from_slice(y_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    AffineG1::new(x, y).map_err(|_| DecodeError::InvalidPoint)
 }
 
-#[allow(dead_code)]
-pub(crate) fn compressed_x_to_g2_point(buf: &[u8]) -> Result<AffineG2, Error> {
+pub(crate) fn unchecked_uncompressed_bytes_to_g1_point(
+    buf: &[u8],
+) -> Result<AffineG1, DecodeError> {
     if buf.len() != 64 {
-        return Err(Error::InvalidXLength);
+        return Err(DecodeError::InvalidLength);
     };
 
-    let (x1, flag) = deserialize_with_flags(&buf[..32])?;
-    let x0 = Fq::from_be_bytes_mod_order(&buf[32..64]).map_err(Error::Field)?;
-    let x = Fq2::new(x0, x1);
+    if buf[0] & MASK != 0 {
+        return Err(DecodeError::UnexpectedFlag);
+    }
+    if is_zeroed(buf[0], &buf[1..])? {
+        return Ok(AffineG1::default());
+    }
+
+    let (x_bytes, y_bytes) = buf.split_at(32);
+
+    let x = Fq::from_slice(x_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let y = Fq::from_slice(y_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    Ok(AffineG1::new_unchecked(x, y))
+}
+
+pub(crate) fn compressed_x_to_g2_point(buf: &[u8]) -> Result<AffineG2, DecodeError> {
+    if buf.len() != 64 {
+        return Err(DecodeError::InvalidLength);
+    };
 
+    let (x1, flag) = deserialize_with_flags(&buf[..32])?;
     if flag == CompressedPointFlag::Infinity {
-        return Ok(AffineG2::one());
+        if !is_zeroed(0, &buf[32..64])? {
+            return Err(DecodeError::InvalidPoint);
+        }
+        return Ok(g2_identity());
     }
 
-    let (y, neg_y) = AffineG2::get_ys_from_x_unchecked(x).ok_or(Error::InvalidPoint)?;
+    let x0 = Fq::from_slice(&buf[32..64]).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let x = Fq2::new(x0, x1);
+    let y = g2_y_from_flag(x, flag)?;
 
-    match flag {
-        CompressedPointFlag::Positive => Ok(AffineG2::new(x, y).map_err(Error::Group)?),
-        CompressedPointFlag::Negative => Ok(AffineG2::new(x, neg_y).map_err(Error::Group)?),
-        _ => Err(Error::InvalidPoint),
-    }
+    AffineG2::new(x, y).map_err(|_| DecodeError::InvalidPoint)
 }
 
-pub(crate) fn unchecked_compressed_x_to_g2_point(buf: &[u8]) -> Result<AffineG2, Error> {
+pub(crate) fn unchecked_compressed_x_to_g2_point(buf: &[u8]) -> Result<AffineG2, DecodeError> {
     if buf.len() != 64 {
-        return Err(Error::InvalidXLength);
+        return Err(DecodeError::InvalidLength);
     };
 
     let (x1, flag) = deserialize_with_flags(&buf[..32])?;
-    let x0 = Fq::from_be_bytes_mod_order(&buf[32..64]).map_err(Error::Field)?;
+    if flag == CompressedPointFlag::Infinity {
+        if !is_zeroed(0, &buf[32..64])? {
+            return Err(DecodeError::InvalidPoint);
+        }
+        return Ok(g2_identity());
+    }
+
+    let x0 = Fq::from_slice(&buf[32..64]).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
     let x = Fq2::new(x0, x1);
+    let y = g2_y_from_flag(x, flag)?;
 
-    if flag == CompressedPointFlag::Infinity {
-        return Ok(AffineG2::one());
+    Ok(AffineG2::new_unchecked(x, y))
+}
+
+// gnark's `E2.LexicographicallyLargest`, adapted from zkcrypto's bls12_381: y is the largest
+// of y and -y if its imaginary part A1 is the largest, or, when A1 is zero, if its real part A0
+// is
+pub(crate) fn is_lexicographically_largest(y: Fq2) -> bool {
+    if y.imaginary().is_zero() {
+        y.real() > -y.real()
+    } else {
+        y.imaginary() > -y.imaginary()
     }
+}
 
-    let (y, neg_y) = AffineG2::get_ys_from_x_unchecked(x).ok_or(Error::InvalidPoint)?;
+// Returns the square root of x³ + b' that the flag of a compressed G2 point designates:
+// the smallest for `Positive`, the largest for `Negative`
+fn g2_y_from_flag(x: Fq2, flag: CompressedPointFlag) -> Result<Fq2, DecodeError> {
+    let (y, _) = AffineG2::get_ys_from_x_unchecked(x).ok_or(DecodeError::InvalidPoint)?;
+    let largest = match flag {
+        CompressedPointFlag::Positive => false,
+        CompressedPointFlag::Negative => true,
+        CompressedPointFlag::Infinity => return Err(DecodeError::InvalidPoint),
+    };
 
-    match flag {
-        CompressedPointFlag::Positive => Ok(AffineG2::new_unchecked(x, y)),
-        CompressedPointFlag::Negative => Ok(AffineG2::new_unchecked(x, neg_y)),
-        _ => Err(Error::InvalidPoint),
+    if is_lexicographically_largest(y) == largest {
+        Ok(y)
+    } else {
+        Ok(-y)
     }
 }
 
-pub(crate) fn uncompressed_bytes_to_g2_point(buf: &[u8]) -> Result<AffineG2, Error> {
+pub(crate) fn uncompressed_bytes_to_g2_point(buf: &[u8]) -> Result<AffineG2, DecodeError> {
     if buf.len() != 128 {
-        return Err(Error::InvalidXLength);
+        return Err(DecodeError::InvalidLength);
+    }
+
+    if buf[0] & MASK != 0 {
+        return Err(DecodeError::UnexpectedFlag);
+    }
+    if is_zeroed(buf[0], &buf[1..])? {
+        return Ok(g2_identity());
     }
 
     let (x_bytes, y_bytes) = buf.split_at(64);
     let (x1_bytes, x0_bytes) = x_bytes.split_at(32);
     let (y1_bytes, y0_bytes) = y_bytes.split_at(32);
 
-    let x1 = Fq::from_slice(x1_bytes).map_err(Error::Field)?;
-    let x0 = Fq::from_slice(x0_bytes).map_err(Error::Field)?;
-    let y1 = Fq::from_slice(y1_bytes).map_err(Error::Field)?;
-    let y0 = Fq::from_slice(y0_bytes).map_err(Error::Field)?;
+    let x1 = Fq::from_slice(x1_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let x0 = Fq::from_slice(x0_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let y1 = Fq::from_slice(y1_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let y0 = Fq::from_slice(y0_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
 
     let x = Fq2::new(x0, x1);
     let y = Fq2::new(y0, y1);
 
-    AffineG2::new(x, y).map_err(Error::Group)
+    AffineG2::new(x, y).map_err(|_| DecodeError::InvalidPoint)
+}
+
+pub(crate) fn unchecked_uncompressed_bytes_to_g2_point(
+    buf: &[u8],
+) -> Result<AffineG2, DecodeError> {
+    if buf.len() != 128 {
+        return Err(DecodeError::InvalidLength);
+    }
+
+    if buf[0] & MASK != 0 {
+        return Err(DecodeError::UnexpectedFlag);
+    }
+    if is_zeroed(buf[0], &buf[1..])? {
+        return Ok(g2_identity());
+    }
+
+    let (x_bytes, y_bytes) = buf.split_at(64);
+    let (x1_bytes, x0_bytes) = x_bytes.split_at(32);
+    let (y1_bytes, y0_bytes) = y_bytes.split_at(32);
+
+    let x1 = Fq::from_slice(x1_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let x0 = Fq::from_slice(x0_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let y1 = Fq::from_slice(y1_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+    let y0 = Fq::from_slice(y0_bytes).map_err(|_| DecodeError::NonCanonicalCoordinate)?;
+
+    let x = Fq2::new(x0, x1);
+    let y = Fq2::new(y0, y1);
+
+    Ok(AffineG2::new_unchecked(x, y))
+}
+
+// gnark writes the point at infinity with zero coordinates. bn reads a zero AffineG1 as the
+// identity, but has no affine G2 identity, so a zero AffineG2 stands for it. It has no pairing
+// lines, see `precompute_lines`.
+pub(crate) fn g2_identity() -> AffineG2 {
+    AffineG2::new_unchecked(Fq2::zero(), Fq2::zero())
+}
+
+pub(crate) fn is_g2_identity(p: &AffineG2) -> bool {
+    p.x().is_zero() && p.y().is_zero()
+}
+
+// Checks a point read with the unchecked converters, naming `field` in the error. G1 has a
+// cofactor of 1, so a point on the curve is in the subgroup.
+pub(crate) fn check_g1_point(p: &AffineG1, field: &'static str) -> Result<(), Error> {
+    if *p == AffineG1::default() {
+        return Ok(());
+    }
+
+    AffineG1::new(p.x(), p.y())
+        .map(|_| ())
+        .map_err(|_| VkError::InvalidPoint(field).into())
+}
+
+// Checks that a point read with the unchecked converters is on the twist and in the subgroup of
+// order r, naming `field` in the error.
+pub(crate) fn check_g2_point(p: &AffineG2, field: &'static str) -> Result<(), Error> {
+    if is_g2_identity(p) {
+        return Ok(());
+    }
+
+    AffineG2::new(p.x(), p.y())
+        .map(|_| ())
+        .map_err(|_| VkError::InvalidPoint(field).into())
+}
+
+pub(crate) fn bytes_to_g1_point(
+    buf: &[u8],
+    encoding: PointEncoding,
+) -> Result<AffineG1, DecodeError> {
+    match encoding {
+        PointEncoding::Compressed => compressed_x_to_g1_point(buf),
+        PointEncoding::Uncompressed => uncompressed_bytes_to_g1_point(buf),
+    }
+}
+
+pub(crate) fn bytes_to_g2_point(
+    buf: &[u8],
+    encoding: PointEncoding,
+) -> Result<AffineG2, DecodeError> {
+    match encoding {
+        PointEncoding::Compressed => compressed_x_to_g2_point(buf),
+        PointEncoding::Uncompressed => uncompressed_bytes_to_g2_point(buf),
+    }
+}
+
+pub(crate) fn unchecked_bytes_to_g1_point(
+    buf: &[u8],
+    encoding: PointEncoding,
+) -> Result<AffineG1, DecodeError> {
+    match encoding {
+        PointEncoding::Compressed => unchecked_compressed_x_to_g1_point(buf),
+        PointEncoding::Uncompressed => unchecked_uncompressed_bytes_to_g1_point(buf),
+    }
+}
+
+pub(crate) fn unchecked_bytes_to_g2_point(
+    buf: &[u8],
+    encoding: PointEncoding,
+) -> Result<AffineG2, DecodeError> {
+    match encoding {
+        PointEncoding::Compressed => unchecked_compressed_x_to_g2_point(buf),
+        PointEncoding::Uncompressed => unchecked_uncompressed_bytes_to_g2_point(buf),
+    }
+}
+
+pub(crate) fn fq_to_bytes(x: Fq) -> [u8; 32] {
+    x.into_u256().to_bytes_be()
+}
+
+// The flag of a compressed G1 point tells whether y is the smallest of y and -y, mirroring
+// `compressed_x_to_g1_point`.
+pub(crate) fn g1_point_to_compressed_bytes(p: &AffineG1) -> [u8; 32] {
+    if *p == AffineG1::default() {
+        let mut bytes = [0u8; 32];
+        bytes[0] = CompressedPointFlag::Infinity.into();
+        return bytes;
+    }
+
+    let y = p.y();
+    let flag = if y.cmp(&-y) == Ordering::Greater {
+        CompressedPointFlag::Negative
+    } else {
+        CompressedPointFlag::Positive
+    };
+
+    let mut bytes = fq_to_bytes(p.x());
+    bytes[0] |= u8::from(flag);
+    bytes
+}
+
+pub(crate) fn g1_point_to_uncompressed_bytes(p: &AffineG1) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if *p == AffineG1::default() {
+        return bytes;
+    }
+
+    bytes[..32].copy_from_slice(&fq_to_bytes(p.x()));
+    bytes[32..].copy_from_slice(&fq_to_bytes(p.y()));
+    bytes
+}
+
+// The flag of a compressed G2 point tells whether y is the lexicographically largest of y and
+// -y, mirroring `compressed_x_to_g2_point`.
+pub(crate) fn g2_point_to_compressed_bytes(p: &AffineG2) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if is_g2_identity(p) {
+        bytes[0] = CompressedPointFlag::Infinity.into();
+        return bytes;
+    }
+
+    let flag = if is_lexicographically_largest(p.y()) {
+        CompressedPointFlag::Negative
+    } else {
+        CompressedPointFlag::Positive
+    };
+
+    let x = p.x();
+    bytes[..32].copy_from_slice(&fq_to_bytes(x.imaginary()));
+    bytes[32..].copy_from_slice(&fq_to_bytes(x.real()));
+    bytes[0] |= u8::from(flag);
+    bytes
+}
+
+pub(crate) fn g2_point_to_uncompressed_bytes(p: &AffineG2) -> [u8; 128] {
+    let (x, y) = (p.x(), p.y());
+
+    let mut bytes = [0u8; 128];
+    bytes[..32].copy_from_slice(&fq_to_bytes(x.imaginary()));
+    bytes[32..64].copy_from_slice(&fq_to_bytes(x.real()));
+    bytes[64..96].copy_from_slice(&fq_to_bytes(y.imaginary()));
+    bytes[96..].copy_from_slice(&fq_to_bytes(y.real()));
+    bytes
+}
+
+pub(crate) fn g1_to_bytes(g1: &AffineG1) -> Result<Vec<u8>, Error> {
+    let mut bytes: [u8; 64] = unsafe { core::mem::transmute(*g1) };
+    bytes[..32].reverse();
+    bytes[32..].reverse();
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::{COMPRESSED_INFINITY, COMPRESSED_NEGATIVE, COMPRESSED_POSTIVE},
+        test_utils::{fr, g1, g2, non_subgroup_g2, off_curve_g1, off_curve_g2},
+    };
+    use bn::Fr;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn fq2(real: Fq, imaginary: Fq) -> Fq2 {
+        Fq2::new(real, imaginary)
+    }
+
+    #[test]
+    fn test_is_lexicographically_largest() {
+        let (zero, one) = (Fq::zero(), Fq::one());
+
+        assert!(!is_lexicographically_largest(fq2(zero, one)));
+        assert!(is_lexicographically_largest(fq2(zero, -one)));
+        // The imaginary part decides, whatever the real part
+        assert!(!is_lexicographically_largest(fq2(-one, one)));
+        assert!(is_lexicographically_largest(fq2(one, -one)));
+        // The real part decides when the imaginary part is zero
+        assert!(!is_lexicographically_largest(fq2(one, zero)));
+        assert!(is_lexicographically_largest(fq2(-one, zero)));
+    }
+
+    #[test]
+    fn test_compressed_g2_flag_follows_the_imaginary_part() {
+        let is_largest = |a: Fq| a > -a;
+
+        // Points whose y has parts on either side of (p - 1) / 2, where deciding on the real
+        // part would pick the other root
+        let mut nb_checked = 0;
+        for k in 1..32 {
+            let p = g2(fr(k));
+            let y = p.y();
+            if is_largest(y.real()) == is_largest(y.imaginary()) {
+                continue;
+            }
+            nb_checked += 1;
+
+            let expected = if is_largest(y.imaginary()) {
+                COMPRESSED_NEGATIVE
+            } else {
+                COMPRESSED_POSTIVE
+            };
+            let bytes = g2_point_to_compressed_bytes(&p);
+            assert_eq!(bytes[0] & MASK, expected);
+            assert!(compressed_x_to_g2_point(&bytes).is_ok_and(|q| q == p));
+        }
+        assert!(nb_checked > 0);
+    }
+
+    #[test]
+    fn test_compressed_g2_flag_follows_gnark_rule() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..8 {
+            let q = g2(Fr::random(&mut rng));
+            for p in [q, -q] {
+                let bytes = g2_point_to_compressed_bytes(&p);
+                let expected = if is_lexicographically_largest(p.y()) {
+                    CompressedPointFlag::Negative
+                } else {
+                    CompressedPointFlag::Positive
+                };
+
+                assert_eq!(bytes[0] & MASK, u8::from(expected));
+                assert!(compressed_x_to_g2_point(&bytes).is_ok_and(|q| q == p));
+                assert!(unchecked_compressed_x_to_g2_point(&bytes).is_ok_and(|q| q == p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_g2_round_trips() {
+        for p in [g2(fr(1)), g2(fr(42)), -g2(fr(42)), g2_identity()] {
+            let compressed = g2_point_to_compressed_bytes(&p);
+            assert!(bytes_to_g2_point(&compressed, PointEncoding::Compressed).is_ok_and(|q| q == p));
+
+            let uncompressed = g2_point_to_uncompressed_bytes(&p);
+            assert!(
+                bytes_to_g2_point(&uncompressed, PointEncoding::Uncompressed).is_ok_and(|q| q == p)
+            );
+        }
+    }
+
+    #[test]
+    fn test_g1_round_trips() {
+        for p in [g1(fr(1)), g1(fr(42)), -g1(fr(42)), AffineG1::default()] {
+            let compressed = g1_point_to_compressed_bytes(&p);
+            assert_eq!(
+                bytes_to_g1_point(&compressed, PointEncoding::Compressed),
+                Ok(p)
+            );
+
+            let uncompressed = g1_point_to_uncompressed_bytes(&p);
+            assert_eq!(
+                bytes_to_g1_point(&uncompressed, PointEncoding::Uncompressed),
+                Ok(p)
+            );
+        }
+    }
+
+    // The base field modulus p, big endian
+    const MODULUS: [u8; 32] = [
+        0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58,
+        0x5d, 0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c,
+        0xfd, 0x47,
+    ];
+
+    #[test]
+    fn test_rejects_coordinates_beyond_the_modulus() {
+        let mut compressed = MODULUS;
+        compressed[0] |= COMPRESSED_POSTIVE;
+        assert_eq!(
+            compressed_x_to_g1_point(&compressed),
+            Err(DecodeError::NonCanonicalCoordinate)
+        );
+
+        let mut uncompressed = g1_point_to_uncompressed_bytes(&AffineG1::one());
+        uncompressed[..32].copy_from_slice(&MODULUS);
+        assert_eq!(
+            uncompressed_bytes_to_g1_point(&uncompressed),
+            Err(DecodeError::NonCanonicalCoordinate)
+        );
+
+        // x₁ carries the flag, x₀ follows
+        let p = g2(fr(42));
+        for offset in [0, 32] {
+            let mut compressed = g2_point_to_compressed_bytes(&p);
+            let flag = compressed[0] & MASK;
+            compressed[offset..offset + 32].copy_from_slice(&MODULUS);
+            compressed[0] |= flag;
+            assert!(matches!(
+                compressed_x_to_g2_point(&compressed),
+                Err(DecodeError::NonCanonicalCoordinate)
+            ));
+        }
+        for offset in [0, 32, 64, 96] {
+            let mut uncompressed = g2_point_to_uncompressed_bytes(&p);
+            uncompressed[offset..offset + 32].copy_from_slice(&MODULUS);
+            assert!(matches!(
+                uncompressed_bytes_to_g2_point(&uncompressed),
+                Err(DecodeError::NonCanonicalCoordinate)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_flag_selects_the_y_coordinate() {
+        let p = g1(fr(42));
+        let mut bytes = g1_point_to_compressed_bytes(&p);
+        bytes[0] ^= COMPRESSED_POSTIVE ^ COMPRESSED_NEGATIVE;
+        assert_eq!(compressed_x_to_g1_point(&bytes), Ok(-p));
+
+        let p = g2(fr(42));
+        let mut bytes = g2_point_to_compressed_bytes(&p);
+        bytes[0] ^= COMPRESSED_POSTIVE ^ COMPRESSED_NEGATIVE;
+        assert!(compressed_x_to_g2_point(&bytes).is_ok_and(|q| q == -p));
+    }
+
+    #[test]
+    fn test_rejects_invalid_flags() {
+        // No flag in a compressed point, an infinity flag on a point, and a flag in a raw point
+        let mut bytes = g1_point_to_compressed_bytes(&g1(fr(42)));
+        bytes[0] &= !MASK;
+        assert_eq!(
+            compressed_x_to_g1_point(&bytes),
+            Err(DecodeError::UnexpectedFlag)
+        );
+        bytes[0] |= COMPRESSED_INFINITY;
+        assert_eq!(
+            compressed_x_to_g1_point(&bytes),
+            Err(DecodeError::InvalidPoint)
+        );
+
+        let mut bytes = g2_point_to_compressed_bytes(&g2(fr(42)));
+        bytes[0] &= !MASK;
+        assert!(matches!(
+            compressed_x_to_g2_point(&bytes),
+            Err(DecodeError::UnexpectedFlag)
+        ));
+        bytes[0] |= COMPRESSED_INFINITY;
+        assert!(matches!(
+            compressed_x_to_g2_point(&bytes),
+            Err(DecodeError::InvalidPoint)
+        ));
+
+        let mut bytes = g1_point_to_uncompressed_bytes(&g1(fr(42)));
+        bytes[0] |= COMPRESSED_POSTIVE;
+        assert_eq!(
+            uncompressed_bytes_to_g1_point(&bytes),
+            Err(DecodeError::UnexpectedFlag)
+        );
+        let mut bytes = g2_point_to_uncompressed_bytes(&g2(fr(42)));
+        bytes[0] |= COMPRESSED_POSTIVE;
+        assert!(matches!(
+            uncompressed_bytes_to_g2_point(&bytes),
+            Err(DecodeError::UnexpectedFlag)
+        ));
+    }
+
+    #[test]
+    fn test_infinity_decodes_to_the_identity() {
+        let mut compressed_g1 = [0u8; 32];
+        compressed_g1[0] = COMPRESSED_INFINITY;
+        let mut compressed_g2 = [0u8; 64];
+        compressed_g2[0] = COMPRESSED_INFINITY;
+
+        assert_eq!(
+            compressed_x_to_g1_point(&compressed_g1),
+            Ok(AffineG1::default())
+        );
+        assert_eq!(
+            unchecked_compressed_x_to_g1_point(&compressed_g1),
+            Ok(AffineG1::default())
+        );
+        assert_eq!(
+            uncompressed_bytes_to_g1_point(&[0; 64]),
+            Ok(AffineG1::default())
+        );
+        assert_eq!(
+            unchecked_uncompressed_bytes_to_g1_point(&[0; 64]),
+            Ok(AffineG1::default())
+        );
+
+        for p in [
+            compressed_x_to_g2_point(&compressed_g2),
+            unchecked_compressed_x_to_g2_point(&compressed_g2),
+            uncompressed_bytes_to_g2_point(&[0; 128]),
+            unchecked_uncompressed_bytes_to_g2_point(&[0; 128]),
+        ] {
+            assert!(p.is_ok_and(|p| is_g2_identity(&p)));
+        }
+
+        // The point at infinity has no coordinates
+        compressed_g2[63] = 1;
+        assert!(matches!(
+            compressed_x_to_g2_point(&compressed_g2),
+            Err(DecodeError::InvalidPoint)
+        ));
+    }
+
+    #[test]
+    fn test_check_g1_point() {
+        assert!(check_g1_point(&AffineG1::one(), "ar").is_ok());
+        assert!(check_g1_point(&AffineG1::default(), "ar").is_ok());
+        assert!(matches!(
+            check_g1_point(&off_curve_g1(), "ar"),
+            Err(Error::InvalidVerifyingKey(VkError::InvalidPoint("ar")))
+        ));
+    }
+
+    #[test]
+    fn test_check_g2_point() {
+        assert!(check_g2_point(&AffineG2::one(), "bs").is_ok());
+        assert!(check_g2_point(&g2_identity(), "bs").is_ok());
+        for p in [off_curve_g2(), non_subgroup_g2()] {
+            assert!(matches!(
+                check_g2_point(&p, "bs"),
+                Err(Error::InvalidVerifyingKey(VkError::InvalidPoint("bs")))
+            ));
+        }
+    }
 }
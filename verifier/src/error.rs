@@ -1,59 +1,254 @@
-use bn::{CurveError, FieldError, GroupError};
+use bn::FieldError;
 use thiserror_no_std::Error;
 
-#[derive(Error, Debug)]
+/// Errors returned by the Groth16 and Plonk verifiers.
+///
+/// A proof is either accepted with `Ok`, or refused with one of three errors a caller can act
+/// on: [`Error::MalformedInput`] when the bytes of a proof or verifying key cannot be decoded,
+/// [`Error::InvalidVerifyingKey`] when the key is degenerate or inconsistent, and
+/// [`Error::ProofRejected`] when the proof does not verify.
+#[derive(Debug, Error)]
 pub enum Error {
-    // Cryptographic Errors
-    #[error("BSB22 Commitment number mismatch")]
-    Bsb22CommitmentMismatch,
-    #[error("Challenge already computed")]
-    ChallengeAlreadyComputed,
-    #[error("Challenge not found")]
-    ChallengeNotFound,
-    #[error("Previous challenge not computed")]
-    PreviousChallengeNotComputed,
-    #[error("Pairing check failed")]
-    PairingCheckFailed,
-    #[error("Invalid point in subgroup check")]
+    /// A proof or verifying key cannot be decoded, or encoded back.
+    #[error("Malformed input at byte {offset} ({field}): {kind}")]
+    MalformedInput {
+        /// The offset of the value in the serialized object.
+        offset: usize,
+        /// The name of the value, such as `ar` or `kzg_g2`.
+        field: &'static str,
+        /// What is wrong with the value.
+        kind: DecodeError,
+    },
+    /// The verifying key is degenerate or inconsistent.
+    #[error("Invalid verifying key: {0}")]
+    InvalidVerifyingKey(VkError),
+    /// The proof does not verify against the verifying key and the public inputs.
+    #[error("Proof rejected: {0}")]
+    ProofRejected(ProofCheck),
+    /// An argument passed to the crate is out of range.
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(ArgumentError),
+    /// An operation failed that cannot fail on the supported inputs.
+    #[error("Internal error: {0}")]
+    Internal(InternalError),
+}
+
+impl Error {
+    pub(crate) fn malformed(offset: usize, field: &'static str, kind: DecodeError) -> Self {
+        Self::MalformedInput {
+            offset,
+            field,
+            kind,
+        }
+    }
+}
+
+impl From<VkError> for Error {
+    fn from(err: VkError) -> Self {
+        Self::InvalidVerifyingKey(err)
+    }
+}
+
+impl From<ProofCheck> for Error {
+    fn from(check: ProofCheck) -> Self {
+        Self::ProofRejected(check)
+    }
+}
+
+impl From<ArgumentError> for Error {
+    fn from(err: ArgumentError) -> Self {
+        Self::InvalidArgument(err)
+    }
+}
+
+impl From<InternalError> for Error {
+    fn from(err: InternalError) -> Self {
+        Self::Internal(err)
+    }
+}
+
+/// Why a value of a serialized proof or verifying key cannot be decoded.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ends in the middle of the value.
+    #[error("Unexpected end of input")]
+    UnexpectedEndOfInput,
+    /// A length prefix announces more items than the input holds.
+    #[error("Invalid length prefix")]
+    InvalidLengthPrefix,
+    /// Bytes are left after the end of the serialized object.
+    #[error("Trailing bytes after the end of the input")]
+    TrailingBytes,
+    /// An integer does not fit the platform.
+    #[error("Integer too large")]
+    IntegerTooLarge,
+    /// The flag bits of a point are not a valid flag for its encoding.
+    #[error("Unexpected flag")]
+    UnexpectedFlag,
+    /// A coordinate is not below the base field modulus.
+    #[error("Coordinate beyond the modulus")]
+    NonCanonicalCoordinate,
+    /// A point is not on the curve, not in the subgroup, or a malformed point at infinity.
+    #[error("Invalid point")]
     InvalidPoint,
+    /// A point is not given the number of bytes of its encoding.
+    #[error("Invalid point length")]
+    InvalidLength,
+}
 
-    // Mathematical Errors
-    #[error("Beyond the modulus")]
-    BeyondTheModulus,
-    #[error("Ell too large")]
-    EllTooLarge,
-    #[error("Inverse not found")]
-    InverseNotFound,
+/// Why a verifying key is refused.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum VkError {
+    /// A point of the key is not on the curve or, for G2, not in the subgroup of order r.
+    #[error("Invalid point {0}")]
+    InvalidPoint(&'static str),
+    /// One of α, β, γ or δ is the identity.
+    #[error("Unexpected identity {0}")]
+    IdentityPoint(&'static str),
+    /// An IC point is the identity.
+    #[error("Unexpected identity IC point {0}")]
+    IdentityIcPoint(usize),
+    /// An IC point is equal to a previous one.
+    #[error("Duplicate IC point {0}")]
+    DuplicateIcPoint(usize),
+    /// γ and δ are equal.
+    #[error("Gamma and delta are equal")]
+    GammaEqualsDelta,
+    /// There are fewer IC points than the constant wire and the commitment hashes need.
+    #[error("Invalid number of IC points: expected at least {expected}, got {got}")]
+    InvalidNumberOfIcPoints {
+        /// The smallest number of IC points.
+        expected: usize,
+        /// The number of IC points of the key.
+        got: usize,
+    },
+    /// The number of commitment keys differs from the number of committed-index arrays.
+    #[error("Invalid number of commitment keys: expected {expected}, got {got}")]
+    InvalidNumberOfCommitmentKeys {
+        /// The number of committed-index arrays.
+        expected: usize,
+        /// The number of commitment keys.
+        got: usize,
+    },
+    /// A committed index does not point to a public input or to the hash of an earlier
+    /// commitment.
+    #[error("Committed index {index} out of the {nb_public_inputs} public inputs")]
+    CommittedIndexOutOfRange {
+        /// The committed index, counting the constant wire.
+        index: u32,
+        /// The number of public inputs, including the hashes of the earlier commitments.
+        nb_public_inputs: usize,
+    },
+    /// The commitment keys do not share the same G, so their proofs of knowledge cannot be
+    /// folded.
+    #[error("Commitment keys with distinct bases")]
+    DistinctCommitmentBases,
+    /// The precomputed pairing lines do not match the G2 points of the KZG verifying key.
+    #[error("Precomputed pairing lines do not match the G2 points")]
+    InvalidPairingLines,
+    /// The domain size is not a power of two.
+    #[error("Invalid domain size {0}: not a power of two")]
+    InvalidDomainSize(usize),
+    /// The inverse of the domain size is wrong.
+    #[error("Invalid domain size inverse")]
+    InvalidDomainSizeInverse,
+    /// The order of the domain generator is not the domain size.
+    #[error("Invalid domain generator: its order is not the domain size")]
+    InvalidDomainGenerator,
+    /// The coset shift or its square is in the domain.
+    #[error("Invalid coset shift: it or its square is in the domain")]
+    InvalidCosetShift,
+    /// A BSB22 commitment constraint, placed after the public inputs, is out of the domain.
+    #[error("Commitment constraint index {index} out of the domain of size {size}")]
+    CommitmentIndexOutOfDomain {
+        /// The commitment constraint index.
+        index: usize,
+        /// The domain size.
+        size: usize,
+    },
+    /// The number of commitment constraint indexes differs from the number of commitment
+    /// selectors.
+    #[error("Invalid number of commitment constraint indexes: expected {expected}, got {got}")]
+    InvalidNumberOfCommitmentIndexes {
+        /// The number of commitment selectors.
+        expected: usize,
+        /// The number of commitment constraint indexes.
+        got: usize,
+    },
+}
+
+/// The check a proof failed.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum ProofCheck {
+    /// The final pairing check.
+    #[error("Pairing check failed")]
+    Pairing,
+    /// The proof of knowledge of the BSB22 commitments of a Groth16 proof.
+    #[error("BSB22 commitment proof of knowledge check failed")]
+    CommitmentProofOfKnowledge,
+    /// The opening of the linearized polynomial of a Plonk proof.
     #[error("Opening linear polynomial mismatch")]
-    OpeningPolyMismatch,
+    LinearizedPolynomialOpening,
+    /// The evaluation challenge of a Plonk proof is 1, where the first Lagrange polynomial
+    /// cannot be evaluated.
+    #[error("Degenerate evaluation challenge")]
+    DegenerateChallenge,
+    /// The number of public inputs differs from the verifying key.
+    #[error("Invalid number of public inputs: expected {expected}, got {got}")]
+    NumberOfPublicInputs {
+        /// The number of public inputs of the verifying key.
+        expected: usize,
+        /// The number of public inputs given.
+        got: usize,
+    },
+    /// The number of BSB22 commitments differs from the verifying key.
+    #[error("Invalid number of BSB22 commitments: expected {expected}, got {got}")]
+    NumberOfBsb22Commitments {
+        /// The number of commitments of the verifying key.
+        expected: usize,
+        /// The number of commitments of the proof.
+        got: usize,
+    },
+    /// The number of claimed values of a Plonk proof differs from the verifying key.
+    #[error("Invalid number of claimed values: expected {expected}, got {got}")]
+    NumberOfClaimedValues {
+        /// The number of polynomials opened at ζ.
+        expected: usize,
+        /// The number of claimed values of the proof.
+        got: usize,
+    },
+    /// The numbers of digests, opening proofs and points of a KZG opening differ.
+    #[error("Invalid number of digests")]
+    NumberOfDigests,
+}
 
-    // Input Errors
+/// Why an argument is refused.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum ArgumentError {
+    /// A domain separator is longer than 255 bytes.
     #[error("DST too large")]
     DSTTooLarge,
-    #[error("Invalid number of digests")]
-    InvalidNumberOfDigests,
-    #[error("Invalid witness")]
-    InvalidWitness,
-    #[error("Invalid x length")]
-    InvalidXLength,
-    #[error("Unexpected flag")]
-    UnexpectedFlag,
-    #[error("Invalid data")]
-    InvalidData,
-
-    // Conversion Errors
-    #[error("Failed to get Fr from random bytes")]
-    FailedToGetFrFromRandomBytes,
-    #[error("Failed to get x")]
-    FailedToGetX,
-    #[error("Failed to get y")]
-    FailedToGetY,
-
-    // External Library Errors
+    /// More bytes are asked from `expand_message_xmd` than 255 blocks of the hash.
+    #[error("Ell too large")]
+    EllTooLarge,
+    /// The gnark release is not supported.
+    #[error("Unsupported format version")]
+    UnsupportedFormatVersion,
+}
+
+/// Errors that the verifiers do not run into on the supported inputs.
+#[derive(Debug, Error)]
+pub enum InternalError {
+    /// A Fiat-Shamir challenge was computed twice.
+    #[error("Challenge already computed")]
+    ChallengeAlreadyComputed,
+    /// A Fiat-Shamir challenge is not part of the transcript.
+    #[error("Challenge not found")]
+    ChallengeNotFound,
+    /// A Fiat-Shamir challenge was used before the ones it depends on.
+    #[error("Previous challenge not computed")]
+    PreviousChallengeNotComputed,
+    /// A scalar field operation failed.
     #[error("BN254 Field Error")]
     Field(FieldError),
-    #[error("BN254 Group Error")]
-    Group(GroupError),
-    #[error("BN254 Curve Error")]
-    Curve(CurveError),
 }
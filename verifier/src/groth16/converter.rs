@@ -1,74 +1,112 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec::Vec;
 use bn::AffineG1;
 
 use crate::{
-    converter::{
-        unchecked_compressed_x_to_g1_point, unchecked_compressed_x_to_g2_point,
-        uncompressed_bytes_to_g1_point, uncompressed_bytes_to_g2_point,
-    },
+    converter::{check_g1_point, check_g2_point, PointEncoding},
+    error::Error,
     groth16::{Groth16G1, Groth16G2, Groth16Proof, Groth16VerifyingKey, PedersenVerifyingKey},
+    reader::{ByteReader, ParseMode, VkValidation},
+    writer::ByteWriter,
 };
 
-use super::error::Groth16Error;
+// Proofs written by gnark's `WriteTo` are compressed, those written by `WriteRawTo` are not.
+// The encoding is detected from the flag bits of the first point.
+pub(crate) fn load_groth16_proof_from_bytes(
+    buffer: &[u8],
+    mode: ParseMode,
+) -> Result<Groth16Proof, Error> {
+    let encoding = PointEncoding::detect(buffer);
+    let mut reader = ByteReader::new(buffer);
+
+    let ar = reader.read_g1(encoding, "ar")?;
+    let bs = reader.read_g2(encoding, "bs")?;
+    let krs = reader.read_g1(encoding, "krs")?;
+
+    // Proofs without BSB22 commitments may stop right after Krs.
+    if reader.is_empty() {
+        return Ok(Groth16Proof {
+            ar,
+            bs,
+            krs,
+            commitments: Vec::new(),
+            commitment_pok: AffineG1::default(),
+            encoding,
+            has_commitment_section: false,
+        });
+    }
+
+    let num_commitments = reader.read_len_u32(encoding.g1_size(), "commitments")?;
+    let mut commitments = Vec::with_capacity(num_commitments);
+    for _ in 0..num_commitments {
+        commitments.push(reader.read_g1(encoding, "commitments")?);
+    }
 
-pub(crate) fn load_groth16_proof_from_bytes(buffer: &[u8]) -> Result<Groth16Proof, Groth16Error> {
-    let ar = uncompressed_bytes_to_g1_point(&buffer[..64])?;
-    let bs = uncompressed_bytes_to_g2_point(&buffer[64..192])?;
-    let krs = uncompressed_bytes_to_g1_point(&buffer[192..256])?;
+    let commitment_pok = reader.read_g1(encoding, "commitment_pok")?;
+
+    reader.finish(mode)?;
 
     Ok(Groth16Proof {
         ar,
         bs,
         krs,
-        commitments: Vec::new(),
-        commitment_pok: AffineG1::one(),
+        commitments,
+        commitment_pok,
+        encoding,
+        has_commitment_section: true,
     })
 }
 
+// Verifying keys are accepted both compressed (`WriteTo`) and uncompressed (`WriteRawTo`).
 pub(crate) fn load_groth16_verifying_key_from_bytes(
     buffer: &[u8],
-) -> Result<Groth16VerifyingKey, Groth16Error> {
-    let g1_alpha = unchecked_compressed_x_to_g1_point(&buffer[..32])?;
-    let g1_beta = unchecked_compressed_x_to_g1_point(&buffer[32..64])?;
-    let g2_beta = unchecked_compressed_x_to_g2_point(&buffer[64..128])?;
-    let g2_gamma = unchecked_compressed_x_to_g2_point(&buffer[128..192])?;
-    let g1_delta = unchecked_compressed_x_to_g1_point(&buffer[192..224])?;
-    let g2_delta = unchecked_compressed_x_to_g2_point(&buffer[224..288])?;
-
-    let num_k = u32::from_be_bytes([buffer[288], buffer[289], buffer[290], buffer[291]]);
-    let mut k = Vec::new();
-    let mut offset = 292;
+    mode: ParseMode,
+    validation: VkValidation,
+) -> Result<Groth16VerifyingKey, Error> {
+    let encoding = PointEncoding::detect(buffer);
+    let mut reader = ByteReader::new(buffer);
+
+    let g1_alpha = reader.read_unchecked_g1(encoding, "alpha_g1")?;
+    let g1_beta = reader.read_unchecked_g1(encoding, "beta_g1")?;
+    let g2_beta = reader.read_unchecked_g2(encoding, "beta_g2")?;
+    let g2_gamma = reader.read_unchecked_g2(encoding, "gamma_g2")?;
+    let g1_delta = reader.read_unchecked_g1(encoding, "delta_g1")?;
+    let g2_delta = reader.read_unchecked_g2(encoding, "delta_g2")?;
+
+    let num_k = reader.read_len_u32(encoding.g1_size(), "k")?;
+    let mut k = Vec::with_capacity(num_k);
     for _ in 0..num_k {
-        let point = unchecked_compressed_x_to_g1_point(&buffer[offset..offset + 32])?;
-        k.push(point);
-        offset += 32;
-    }
-
-    let num_of_array_of_public_and_commitment_committed = u32::from_be_bytes([
-        buffer[offset],
-        buffer[offset + 1],
-        buffer[offset + 2],
-        buffer[offset + 3],
-    ]);
-    offset += 4;
+        k.push(reader.read_unchecked_g1(encoding, "k")?);
+    }
+
+    let num_of_array_of_public_and_commitment_committed =
+        reader.read_len_u32(4, "public_and_commitment_committed")?;
+    let mut public_and_commitment_committed =
+        Vec::with_capacity(num_of_array_of_public_and_commitment_committed);
     for _ in 0..num_of_array_of_public_and_commitment_committed {
-        let num = u32::from_be_bytes([
-            buffer[offset],
-            buffer[offset + 1],
-            buffer[offset + 2],
-            buffer[offset + 3],
-        ]);
-        offset += 4;
+        // Indexes are serialized by gnark as u64 values.
+        let num = reader.read_len_u32(8, "public_and_commitment_committed")?;
+        let mut committed = Vec::with_capacity(num);
         for _ in 0..num {
-            offset += 4;
+            committed.push(reader.read_u64_as("public_and_commitment_committed")?);
         }
+        public_and_commitment_committed.push(committed);
+    }
+
+    let num_commitment_keys = reader.read_len_u32(2 * encoding.g2_size(), "commitment_keys")?;
+    let mut commitment_keys = Vec::with_capacity(num_commitment_keys);
+    for _ in 0..num_commitment_keys {
+        let g = reader.read_unchecked_g2(encoding, "commitment_keys.g")?;
+        let g_root_sigma_neg =
+            reader.read_unchecked_g2(encoding, "commitment_keys.g_root_sigma_neg")?;
+        commitment_keys.push(PedersenVerifyingKey {
+            g,
+            g_root_sigma_neg,
+        });
     }
 
-    let commitment_key_g = unchecked_compressed_x_to_g2_point(&buffer[offset..offset + 64])?;
-    let commitment_key_g_root_sigma_neg =
-        unchecked_compressed_x_to_g2_point(&buffer[offset + 64..offset + 128])?;
+    reader.finish(mode)?;
 
-    Ok(Groth16VerifyingKey {
+    let vk = Groth16VerifyingKey {
         g1: Groth16G1 {
             alpha: g1_alpha,
             beta: -g1_beta,
@@ -80,10 +118,305 @@ pub(crate) fn load_groth16_verifying_key_from_bytes(
             gamma: g2_gamma,
             delta: g2_delta,
         },
-        commitment_key: PedersenVerifyingKey {
-            g: commitment_key_g,
-            g_root_sigma_neg: commitment_key_g_root_sigma_neg,
+        commitment_keys,
+        public_and_commitment_committed,
+        encoding,
+    };
+
+    if validation == VkValidation::Full {
+        check_verifying_key_points(&vk)?;
+        vk.validate()?;
+    }
+
+    Ok(vk)
+}
+
+fn check_verifying_key_points(vk: &Groth16VerifyingKey) -> Result<(), Error> {
+    check_g1_point(&vk.g1.alpha, "alpha_g1")?;
+    check_g1_point(&vk.g1.beta, "beta_g1")?;
+    check_g1_point(&vk.g1.delta, "delta_g1")?;
+    for k in &vk.g1.k {
+        check_g1_point(k, "k")?;
+    }
+
+    check_g2_point(&vk.g2.beta, "beta_g2")?;
+    check_g2_point(&vk.g2.gamma, "gamma_g2")?;
+    check_g2_point(&vk.g2.delta, "delta_g2")?;
+    for key in &vk.commitment_keys {
+        check_g2_point(&key.g, "commitment_keys.g")?;
+        check_g2_point(&key.g_root_sigma_neg, "commitment_keys.g_root_sigma_neg")?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn groth16_proof_to_bytes(proof: &Groth16Proof) -> Result<Vec<u8>, Error> {
+    let encoding = proof.encoding;
+    let mut writer = ByteWriter::new();
+
+    writer.write_g1(&proof.ar, encoding);
+    writer.write_g2(&proof.bs, encoding);
+    writer.write_g1(&proof.krs, encoding);
+
+    if proof.has_commitment_section {
+        writer.write_len_u32(proof.commitments.len(), "commitments")?;
+        for commitment in &proof.commitments {
+            writer.write_g1(commitment, encoding);
+        }
+        writer.write_g1(&proof.commitment_pok, encoding);
+    }
+
+    Ok(writer.into_bytes())
+}
+
+pub(crate) fn groth16_verifying_key_to_bytes(vk: &Groth16VerifyingKey) -> Result<Vec<u8>, Error> {
+    let encoding = vk.encoding;
+    let mut writer = ByteWriter::new();
+
+    // β is stored negated
+    writer.write_g1(&vk.g1.alpha, encoding);
+    writer.write_g1(&-vk.g1.beta, encoding);
+    writer.write_g2(&-vk.g2.beta, encoding);
+    writer.write_g2(&vk.g2.gamma, encoding);
+    writer.write_g1(&vk.g1.delta, encoding);
+    writer.write_g2(&vk.g2.delta, encoding);
+
+    writer.write_len_u32(vk.g1.k.len(), "k")?;
+    for k in &vk.g1.k {
+        writer.write_g1(k, encoding);
+    }
+
+    writer.write_len_u32(
+        vk.public_and_commitment_committed.len(),
+        "public_and_commitment_committed",
+    )?;
+    for committed in &vk.public_and_commitment_committed {
+        writer.write_len_u32(committed.len(), "public_and_commitment_committed")?;
+        for index in committed {
+            writer.write_u64(u64::from(*index));
+        }
+    }
+
+    writer.write_len_u32(vk.commitment_keys.len(), "commitment_keys")?;
+    for key in &vk.commitment_keys {
+        writer.write_g2(&key.g, encoding);
+        writer.write_g2(&key.g_root_sigma_neg, encoding);
+    }
+
+    Ok(writer.into_bytes())
+}
+
+impl Groth16Proof {
+    /// Parses a proof serialized by gnark, with compressed or uncompressed points.
+    ///
+    /// Unlike [`Groth16Verifier::verify`](crate::Groth16Verifier::verify), bytes left after
+    /// the proof are rejected, so that [`Self::to_gnark_bytes`] gives back the same bytes.
+    pub fn from_gnark_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        load_groth16_proof_from_bytes(bytes, ParseMode::Strict)
+    }
+
+    /// Serializes the proof in gnark's format, with its point encoding.
+    pub fn to_gnark_bytes(&self) -> Result<Vec<u8>, Error> {
+        groth16_proof_to_bytes(self)
+    }
+}
+
+impl Groth16VerifyingKey {
+    /// Parses a verifying key serialized by gnark, with compressed or uncompressed points.
+    ///
+    /// Bytes left after the key are rejected, so that [`Self::to_gnark_bytes`] gives back the
+    /// same bytes.
+    pub fn from_gnark_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        load_groth16_verifying_key_from_bytes(bytes, ParseMode::Strict, VkValidation::Trusted)
+    }
+
+    /// Parses a verifying key like [`Self::from_gnark_bytes`], checking its points as set by
+    /// `validation`.
+    pub fn from_gnark_bytes_with_validation(
+        bytes: &[u8],
+        validation: VkValidation,
+    ) -> Result<Self, Error> {
+        load_groth16_verifying_key_from_bytes(bytes, ParseMode::Strict, validation)
+    }
+
+    /// Serializes the verifying key in gnark's format, with its point encoding.
+    pub fn to_gnark_bytes(&self) -> Result<Vec<u8>, Error> {
+        groth16_verifying_key_to_bytes(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        converter::{g1_point_to_uncompressed_bytes, g2_point_to_uncompressed_bytes},
+        error::VkError,
+        test_utils::{
+            fr, g1, g2, groth16_commitment_vk, groth16_vk, non_subgroup_g2, off_curve_g1,
+            off_curve_g2,
         },
-        public_and_commitment_committed: vec![vec![0u32; 0]],
-    })
+    };
+    use alloc::vec;
+
+    // The raw gnark proof of the SP1 fibonacci example, written with `WriteRawTo`
+    const FIBONACCI_PROOF: &[u8] = include_bytes!("../../fixtures/fibonacci_groth16_proof.raw");
+
+    fn groth16_vk_with_commitment() -> Groth16VerifyingKey {
+        let vk = groth16_vk();
+        Groth16VerifyingKey::from_parts(
+            vk.alpha_g1(),
+            vk.beta_g1(),
+            vk.beta_g2(),
+            vk.gamma_g2(),
+            vk.delta_g1(),
+            vk.delta_g2(),
+            vec![g1(fr(11)), g1(fr(13)), g1(fr(17)), g1(fr(19))],
+            vec![PedersenVerifyingKey::new(g2(fr(23)), -g2(fr(29)))],
+            vec![vec![1]],
+        )
+    }
+
+    #[test]
+    fn test_verifying_key_round_trips() {
+        for vk in [groth16_vk(), groth16_vk_with_commitment()] {
+            for encoding in [PointEncoding::Compressed, PointEncoding::Uncompressed] {
+                let vk = vk.clone().with_encoding(encoding);
+                let bytes = vk.to_gnark_bytes().unwrap();
+                let parsed = Groth16VerifyingKey::from_gnark_bytes(&bytes).unwrap();
+
+                assert!(parsed == vk);
+                assert_eq!(parsed.to_gnark_bytes().unwrap(), bytes);
+            }
+        }
+    }
+
+    // The offsets of [α]₁, [γ]₂ and the first commitment key G of an uncompressed verifying key
+    // with two public inputs and one commitment to the first
+    const UNCOMPRESSED_ALPHA_G1_OFFSET: usize = 0;
+    const UNCOMPRESSED_GAMMA_G2_OFFSET: usize = 2 * 64 + 128;
+    const UNCOMPRESSED_COMMITMENT_G_OFFSET: usize = 3 * 64 + 3 * 128 + 4 + 4 * 64 + 4 + 4 + 8 + 4;
+
+    fn uncompressed_bytes_with(offset: usize, point: &[u8]) -> Vec<u8> {
+        let mut bytes = groth16_commitment_vk()
+            .with_encoding(PointEncoding::Uncompressed)
+            .to_gnark_bytes()
+            .unwrap();
+        bytes[offset..offset + point.len()].copy_from_slice(point);
+        bytes
+    }
+
+    fn load(bytes: &[u8], validation: VkValidation) -> Result<Groth16VerifyingKey, Error> {
+        load_groth16_verifying_key_from_bytes(bytes, ParseMode::Strict, validation)
+    }
+
+    #[test]
+    fn test_full_validation_rejects_off_curve_g1_point() {
+        let bytes = uncompressed_bytes_with(
+            UNCOMPRESSED_ALPHA_G1_OFFSET,
+            &g1_point_to_uncompressed_bytes(&off_curve_g1()),
+        );
+
+        assert!(load(&bytes, VkValidation::Trusted).is_ok());
+        assert!(matches!(
+            load(&bytes, VkValidation::Full),
+            Err(Error::InvalidVerifyingKey(VkError::InvalidPoint(
+                "alpha_g1"
+            )))
+        ));
+    }
+
+    #[test]
+    fn test_full_validation_rejects_invalid_g2_points() {
+        for point in [off_curve_g2(), non_subgroup_g2()] {
+            let point = g2_point_to_uncompressed_bytes(&point);
+
+            let bytes = uncompressed_bytes_with(UNCOMPRESSED_GAMMA_G2_OFFSET, &point);
+            assert!(load(&bytes, VkValidation::Trusted).is_ok());
+            assert!(matches!(
+                load(&bytes, VkValidation::Full),
+                Err(Error::InvalidVerifyingKey(VkError::InvalidPoint(
+                    "gamma_g2"
+                )))
+            ));
+
+            let bytes = uncompressed_bytes_with(UNCOMPRESSED_COMMITMENT_G_OFFSET, &point);
+            assert!(matches!(
+                load(&bytes, VkValidation::Full),
+                Err(Error::InvalidVerifyingKey(VkError::InvalidPoint(
+                    "commitment_keys.g"
+                )))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_full_validation_validates_the_key() {
+        let vk = groth16_vk();
+        let bytes = uncompressed_bytes_with(
+            UNCOMPRESSED_GAMMA_G2_OFFSET,
+            &g2_point_to_uncompressed_bytes(&vk.delta_g2()),
+        );
+
+        assert!(load(&bytes, VkValidation::Trusted).is_ok());
+        assert!(matches!(
+            load(&bytes, VkValidation::Full),
+            Err(Error::InvalidVerifyingKey(VkError::GammaEqualsDelta))
+        ));
+    }
+
+    #[test]
+    fn test_verifying_key_sizes() {
+        // α, β, δ and 3 IC points in G1, β, γ and δ in G2, and the two empty length prefixes
+        let vk = groth16_vk();
+        let compressed = vk.to_gnark_bytes().unwrap();
+        assert_eq!(compressed.len(), 6 * 32 + 3 * 64 + 3 * 4);
+
+        let uncompressed = vk
+            .with_encoding(PointEncoding::Uncompressed)
+            .to_gnark_bytes()
+            .unwrap();
+        assert_eq!(uncompressed.len(), 6 * 64 + 3 * 128 + 3 * 4);
+    }
+
+    #[test]
+    fn test_gnark_proof_round_trips() {
+        let proof = Groth16Proof::from_gnark_bytes(FIBONACCI_PROOF).unwrap();
+        assert_eq!(proof.encoding(), PointEncoding::Uncompressed);
+        assert_eq!(proof.to_gnark_bytes().unwrap(), FIBONACCI_PROOF);
+
+        let compressed = proof.clone().with_encoding(PointEncoding::Compressed);
+        let bytes = compressed.to_gnark_bytes().unwrap();
+        assert_eq!(
+            bytes.len(),
+            32 + 64 + 32 + 4 + proof.commitments().len() * 32 + 32
+        );
+
+        let parsed = Groth16Proof::from_gnark_bytes(&bytes).unwrap();
+        assert!(parsed == compressed);
+        assert_eq!(parsed.to_gnark_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_proof_round_trips() {
+        let proof =
+            Groth16Proof::from_parts(g1(fr(2)), g2(fr(3)), g1(fr(5)), vec![g1(fr(7))], g1(fr(11)));
+
+        for encoding in [PointEncoding::Compressed, PointEncoding::Uncompressed] {
+            let proof = proof.clone().with_encoding(encoding);
+            let bytes = proof.to_gnark_bytes().unwrap();
+            let parsed = Groth16Proof::from_gnark_bytes(&bytes).unwrap();
+
+            assert!(parsed == proof);
+            assert_eq!(parsed.to_gnark_bytes().unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_proof_without_commitment_section_round_trips() {
+        let bytes = &FIBONACCI_PROOF[..64 + 128 + 64];
+        let proof = Groth16Proof::from_gnark_bytes(bytes).unwrap();
+
+        assert!(proof.commitments().is_empty());
+        assert_eq!(proof.to_gnark_bytes().unwrap(), bytes);
+    }
 }
@@ -1,6 +1,6 @@
 mod converter;
-pub(crate) mod error;
 mod verify;
 
 pub(crate) use converter::{load_groth16_proof_from_bytes, load_groth16_verifying_key_from_bytes};
 pub(crate) use verify::*;
+pub use verify::{Groth16Proof, Groth16VerifyingKey, PedersenVerifyingKey, PreparedVerifyingKey};
@@ -1,78 +1,1110 @@
-use alloc::vec::Vec;
-use bn::{pairing, pairing_batch, AffineG1, AffineG2, Fr, Gt, G1, G2};
+use alloc::{vec, vec::Vec};
+use bn::{AffineG1, AffineG2, Fr, Group, G1, G2};
+use core::{fmt, marker::PhantomData};
+use sha2::Sha256;
 
-use super::error::Groth16Error;
+use crate::{
+    constants::{
+        GROTH16_BATCH_CHALLENGE, GROTH16_COMMITMENT_CHALLENGE_DST, GROTH16_COMMITMENT_DST,
+    },
+    converter::{g1_to_bytes, g2_point_to_uncompressed_bytes, is_g2_identity, PointEncoding},
+    error::{Error, InternalError, ProofCheck, VkError},
+    hash_to_field::{hash_to_field, HashToField, WrappedHashToField},
+    pairing::{pairing_check_fixed_q, precompute_lines, PrecomputedLines},
+    transcript::Transcript,
+};
 
 #[derive(Clone, PartialEq)]
-pub struct Groth16G1 {
-    pub alpha: AffineG1,
-    pub beta: AffineG1,
-    pub delta: AffineG1,
-    pub k: Vec<AffineG1>,
+pub(crate) struct Groth16G1 {
+    pub(crate) alpha: AffineG1,
+    // Stored negated
+    pub(crate) beta: AffineG1,
+    pub(crate) delta: AffineG1,
+    pub(crate) k: Vec<AffineG1>,
 }
 
 #[derive(Clone, PartialEq)]
-pub struct Groth16G2 {
-    pub beta: AffineG2,
-    pub delta: AffineG2,
-    pub gamma: AffineG2,
+pub(crate) struct Groth16G2 {
+    // Stored negated
+    pub(crate) beta: AffineG2,
+    pub(crate) delta: AffineG2,
+    pub(crate) gamma: AffineG2,
 }
 
+/// The Pedersen verifying key of a BSB22 commitment in a Groth16 verifying key.
 #[derive(Clone, PartialEq)]
 pub struct PedersenVerifyingKey {
-    pub g: AffineG2,
-    pub g_root_sigma_neg: AffineG2,
+    pub(crate) g: AffineG2,
+    pub(crate) g_root_sigma_neg: AffineG2,
 }
 
+impl PedersenVerifyingKey {
+    /// Creates a Pedersen verifying key from its two G2 points.
+    pub fn new(g: AffineG2, g_root_sigma_neg: AffineG2) -> Self {
+        Self {
+            g,
+            g_root_sigma_neg,
+        }
+    }
+
+    /// Returns the point G.
+    pub fn g(&self) -> AffineG2 {
+        self.g
+    }
+
+    /// Returns the point GRootSigmaNeg, -[1/σ]G.
+    pub fn g_root_sigma_neg(&self) -> AffineG2 {
+        self.g_root_sigma_neg
+    }
+}
+
+impl fmt::Debug for PedersenVerifyingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PedersenVerifyingKey")
+            .field("g", &G2::from(self.g))
+            .field("g_root_sigma_neg", &G2::from(self.g_root_sigma_neg))
+            .finish()
+    }
+}
+
+/// A Groth16 verifying key, as serialized by gnark.
 #[derive(Clone, PartialEq)]
 pub struct Groth16VerifyingKey {
-    pub g1: Groth16G1,
-    pub g2: Groth16G2,
-    pub commitment_key: PedersenVerifyingKey,
-    pub public_and_commitment_committed: Vec<Vec<u32>>,
+    pub(crate) g1: Groth16G1,
+    pub(crate) g2: Groth16G2,
+    pub(crate) commitment_keys: Vec<PedersenVerifyingKey>,
+    pub(crate) public_and_commitment_committed: Vec<Vec<u32>>,
+    pub(crate) encoding: PointEncoding,
 }
 
-#[allow(dead_code)]
-pub struct Groth16Proof {
-    pub ar: AffineG1,
-    pub krs: AffineG1,
-    pub bs: AffineG2,
-    pub commitments: Vec<AffineG1>,
-    pub commitment_pok: AffineG1,
+impl Groth16VerifyingKey {
+    /// Creates a verifying key from its points, as they appear in gnark's `VerifyingKey`.
+    ///
+    /// The key is serialized with compressed points by [`Self::to_gnark_bytes`], like gnark's
+    /// `WriteTo`, unless another encoding is set with [`Self::with_encoding`].
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - The IC points, one for the constant wire, then one per public input and per
+    ///   commitment hash
+    /// * `commitment_keys` - The Pedersen verifying key of each BSB22 commitment
+    /// * `public_and_commitment_committed` - For each commitment, the indexes of the public
+    ///   inputs it commits to, counting the constant wire
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_parts(
+        alpha_g1: AffineG1,
+        beta_g1: AffineG1,
+        beta_g2: AffineG2,
+        gamma_g2: AffineG2,
+        delta_g1: AffineG1,
+        delta_g2: AffineG2,
+        k: Vec<AffineG1>,
+        commitment_keys: Vec<PedersenVerifyingKey>,
+        public_and_commitment_committed: Vec<Vec<u32>>,
+    ) -> Self {
+        Self {
+            g1: Groth16G1 {
+                alpha: alpha_g1,
+                beta: -beta_g1,
+                delta: delta_g1,
+                k,
+            },
+            g2: Groth16G2 {
+                beta: -beta_g2,
+                gamma: gamma_g2,
+                delta: delta_g2,
+            },
+            commitment_keys,
+            public_and_commitment_committed,
+            encoding: PointEncoding::Compressed,
+        }
+    }
+
+    /// Sets the point encoding used by [`Self::to_gnark_bytes`].
+    pub fn with_encoding(mut self, encoding: PointEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns [α]₁.
+    pub fn alpha_g1(&self) -> AffineG1 {
+        self.g1.alpha
+    }
+
+    /// Returns [β]₁.
+    pub fn beta_g1(&self) -> AffineG1 {
+        -self.g1.beta
+    }
+
+    /// Returns [β]₂.
+    pub fn beta_g2(&self) -> AffineG2 {
+        -self.g2.beta
+    }
+
+    /// Returns [γ]₂.
+    pub fn gamma_g2(&self) -> AffineG2 {
+        self.g2.gamma
+    }
+
+    /// Returns [δ]₁.
+    pub fn delta_g1(&self) -> AffineG1 {
+        self.g1.delta
+    }
+
+    /// Returns [δ]₂.
+    pub fn delta_g2(&self) -> AffineG2 {
+        self.g2.delta
+    }
+
+    /// Returns the IC points.
+    pub fn k(&self) -> &[AffineG1] {
+        &self.g1.k
+    }
+
+    /// Returns the Pedersen verifying key of each BSB22 commitment.
+    pub fn commitment_keys(&self) -> &[PedersenVerifyingKey] {
+        &self.commitment_keys
+    }
+
+    /// Returns, for each commitment, the indexes of the public inputs it commits to.
+    pub fn public_and_commitment_committed(&self) -> &[Vec<u32>] {
+        &self.public_and_commitment_committed
+    }
+
+    /// Returns the point encoding of the serialized key.
+    pub fn encoding(&self) -> PointEncoding {
+        self.encoding
+    }
+
+    /// Checks that the verifying key is not degenerate, as a key with such points would let
+    /// proofs be forged, and that its parts are consistent with each other.
+    ///
+    /// The points are not checked to be on the curve, see [`VkValidation`](crate::VkValidation)
+    /// for that.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if none of α, β, γ and δ is the identity, if γ and δ differ, if the IC points
+    /// are distinct and none of them is the identity, if there is one commitment key and one
+    /// IC point per committed-index array, and if every committed index is a public input or
+    /// the hash of an earlier commitment.
+    /// Otherwise, an [`Error::InvalidVerifyingKey`] describing the first failed check.
+    pub fn validate(&self) -> Result<(), Error> {
+        for (point, name) in [
+            (&self.g1.alpha, "alpha_g1"),
+            (&self.g1.beta, "beta_g1"),
+            (&self.g1.delta, "delta_g1"),
+        ] {
+            if *point == AffineG1::default() {
+                return Err(VkError::IdentityPoint(name).into());
+            }
+        }
+        for (point, name) in [
+            (&self.g2.beta, "beta_g2"),
+            (&self.g2.gamma, "gamma_g2"),
+            (&self.g2.delta, "delta_g2"),
+        ] {
+            if is_g2_identity(point) {
+                return Err(VkError::IdentityPoint(name).into());
+            }
+        }
+
+        if self.g2.gamma == self.g2.delta {
+            return Err(VkError::GammaEqualsDelta.into());
+        }
+
+        for (i, k) in self.g1.k.iter().enumerate() {
+            if *k == AffineG1::default() {
+                return Err(VkError::IdentityIcPoint(i).into());
+            }
+            if self.g1.k[..i].contains(k) {
+                return Err(VkError::DuplicateIcPoint(i).into());
+            }
+        }
+
+        let nb_commitments = self.public_and_commitment_committed.len();
+        if self.commitment_keys.len() != nb_commitments {
+            return Err(VkError::InvalidNumberOfCommitmentKeys {
+                expected: nb_commitments,
+                got: self.commitment_keys.len(),
+            }
+            .into());
+        }
+
+        // One IC point for the constant wire, then one per public input and per commitment hash
+        if self.g1.k.len() < nb_commitments + 1 {
+            return Err(VkError::InvalidNumberOfIcPoints {
+                expected: nb_commitments + 1,
+                got: self.g1.k.len(),
+            }
+            .into());
+        }
+
+        // Committed indexes count the constant wire, public inputs start at 1. A commitment may
+        // also commit to the hashes of the earlier ones, which follow the public inputs.
+        let nb_public_inputs = self.g1.k.len() - 1 - nb_commitments;
+        for (i, committed) in self.public_and_commitment_committed.iter().enumerate() {
+            for index in committed {
+                if *index == 0 || *index as usize > nb_public_inputs + i {
+                    return Err(VkError::CommittedIndexOutOfRange {
+                        index: *index,
+                        nb_public_inputs: nb_public_inputs + i,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Groth16VerifyingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Groth16VerifyingKey")
+            .field("alpha_g1", &self.alpha_g1())
+            .field("beta_g1", &self.beta_g1())
+            .field("beta_g2", &G2::from(self.beta_g2()))
+            .field("gamma_g2", &G2::from(self.gamma_g2()))
+            .field("delta_g1", &self.delta_g1())
+            .field("delta_g2", &G2::from(self.delta_g2()))
+            .field("k", &self.g1.k)
+            .field("commitment_keys", &self.commitment_keys)
+            .field(
+                "public_and_commitment_committed",
+                &self.public_and_commitment_committed,
+            )
+            .field("encoding", &self.encoding)
+            .finish()
+    }
 }
 
+/// A Groth16 proof, as serialized by gnark.
 #[derive(Clone, PartialEq)]
-pub struct PreparedVerifyingKey {
-    pub vk: Groth16VerifyingKey,
-    pub alpha_g1_beta_g2: Gt,
-    pub gamma_g2_neg_pc: G2,
-    pub delta_g2_neg_pc: G2,
+pub struct Groth16Proof {
+    pub(crate) ar: AffineG1,
+    pub(crate) krs: AffineG1,
+    pub(crate) bs: AffineG2,
+    pub(crate) commitments: Vec<AffineG1>,
+    pub(crate) commitment_pok: AffineG1,
+    pub(crate) encoding: PointEncoding,
+    // Proofs without commitments may be serialized without the commitment section
+    pub(crate) has_commitment_section: bool,
 }
 
-// Prepare the inputs for the Groth16 verification by combining the public inputs with the corresponding elements of the verification key.
-fn prepare_inputs(vk: Groth16VerifyingKey, public_inputs: &[Fr]) -> Result<G1, Groth16Error> {
-    if (public_inputs.len() + 1) != vk.g1.k.len() {
-        return Err(Groth16Error::PrepareInputsFailed);
+impl Groth16Proof {
+    /// Creates a proof from its points.
+    ///
+    /// The proof is serialized with uncompressed points by [`Self::to_gnark_bytes`], like
+    /// gnark's `WriteRawTo`, unless another encoding is set with [`Self::with_encoding`].
+    ///
+    /// # Arguments
+    ///
+    /// * `commitments` - The BSB22 commitments, empty if the circuit has none
+    /// * `commitment_pok` - The folded proof of knowledge of the commitments
+    pub fn from_parts(
+        ar: AffineG1,
+        bs: AffineG2,
+        krs: AffineG1,
+        commitments: Vec<AffineG1>,
+        commitment_pok: AffineG1,
+    ) -> Self {
+        Self {
+            ar,
+            krs,
+            bs,
+            commitments,
+            commitment_pok,
+            encoding: PointEncoding::Uncompressed,
+            has_commitment_section: true,
+        }
     }
 
-    Ok(public_inputs
+    /// Sets the point encoding used by [`Self::to_gnark_bytes`].
+    pub fn with_encoding(mut self, encoding: PointEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns [A]₁.
+    pub fn ar(&self) -> AffineG1 {
+        self.ar
+    }
+
+    /// Returns [B]₂.
+    pub fn bs(&self) -> AffineG2 {
+        self.bs
+    }
+
+    /// Returns [C]₁.
+    pub fn krs(&self) -> AffineG1 {
+        self.krs
+    }
+
+    /// Returns the BSB22 commitments.
+    pub fn commitments(&self) -> &[AffineG1] {
+        &self.commitments
+    }
+
+    /// Returns the proof of knowledge of the commitments.
+    pub fn commitment_pok(&self) -> AffineG1 {
+        self.commitment_pok
+    }
+
+    /// Returns the point encoding of the serialized proof.
+    pub fn encoding(&self) -> PointEncoding {
+        self.encoding
+    }
+}
+
+impl fmt::Debug for Groth16Proof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Groth16Proof")
+            .field("ar", &self.ar)
+            .field("bs", &G2::from(self.bs))
+            .field("krs", &self.krs)
+            .field("commitments", &self.commitments)
+            .field("commitment_pok", &self.commitment_pok)
+            .field("encoding", &self.encoding)
+            .finish()
+    }
+}
+
+/// A Groth16 verifying key with the proof-independent pairing work done once, so it can be
+/// reused across many verifications.
+///
+/// The Miller loop lines of -γ, -δ and β are precomputed, like gnark-crypto's
+/// `PrecomputeLines`, and so are those of the Pedersen keys of the BSB22 commitments. A proof
+/// is then checked with a single fixed-point Miller loop and final exponentiation:
+///
+/// e(Ar, Bs) * e(-PI, -γ) * e(Krs, -δ) * e(-α, β) == 1
+///
+/// The BSB22 commitments of the proofs are hashed with `F`, gnark's `HashToFieldFn` verifier
+/// option.
+pub struct PreparedVerifyingKey<F = WrappedHashToField> {
+    pub(crate) vk: Groth16VerifyingKey,
+    pub(crate) alpha_g1_neg: AffineG1,
+    pub(crate) beta_g2_lines: PrecomputedLines,
+    pub(crate) gamma_g2_neg_lines: PrecomputedLines,
+    pub(crate) delta_g2_neg_lines: PrecomputedLines,
+    // The lines of G, then of each Gσ⁻¹ᵢ, empty without commitments
+    pub(crate) commitment_key_lines: Vec<PrecomputedLines>,
+    pub(crate) hash_to_field: PhantomData<F>,
+}
+
+impl<F> Clone for PreparedVerifyingKey<F> {
+    fn clone(&self) -> Self {
+        Self {
+            vk: self.vk.clone(),
+            alpha_g1_neg: self.alpha_g1_neg,
+            beta_g2_lines: self.beta_g2_lines,
+            gamma_g2_neg_lines: self.gamma_g2_neg_lines,
+            delta_g2_neg_lines: self.delta_g2_neg_lines,
+            commitment_key_lines: self.commitment_key_lines.clone(),
+            hash_to_field: PhantomData,
+        }
+    }
+}
+
+// The lines and -α are derived from the key, so comparing the keys is enough
+impl<F> PartialEq for PreparedVerifyingKey<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vk == other.vk
+    }
+}
+
+impl<F> fmt::Debug for PreparedVerifyingKey<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedVerifyingKey")
+            .field("nb_ic_points", &self.vk.g1.k.len())
+            .field("nb_commitments", &self.vk.commitment_keys.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Precomputes the Miller loop lines of the G2 points of the verifying key.
+///
+/// # Returns
+///
+/// The prepared key, or an [`Error::InvalidVerifyingKey`] if one of its G2 points is the
+/// identity, which has no lines.
+pub fn prepare_verifying_key<F: HashToField>(
+    vk: Groth16VerifyingKey,
+) -> Result<PreparedVerifyingKey<F>, Error> {
+    let lines = |q: &AffineG2, name| precompute_lines(q).ok_or(VkError::IdentityPoint(name));
+
+    // β is stored negated
+    let beta_g2_lines = lines(&vk.g2.beta, "beta_g2")?;
+    let gamma_g2_neg_lines = lines(&-vk.g2.gamma, "gamma_g2")?;
+    let delta_g2_neg_lines = lines(&-vk.g2.delta, "delta_g2")?;
+
+    let mut commitment_key_lines = Vec::with_capacity(vk.commitment_keys.len() + 1);
+    if let Some(key) = vk.commitment_keys.first() {
+        commitment_key_lines.push(lines(&key.g, "commitment_key_g")?);
+    }
+    for key in &vk.commitment_keys {
+        commitment_key_lines.push(lines(
+            &key.g_root_sigma_neg,
+            "commitment_key_g_root_sigma_neg",
+        )?);
+    }
+
+    Ok(PreparedVerifyingKey {
+        alpha_g1_neg: -vk.g1.alpha,
+        vk,
+        beta_g2_lines,
+        gamma_g2_neg_lines,
+        delta_g2_neg_lines,
+        commitment_key_lines,
+        hash_to_field: PhantomData,
+    })
+}
+
+fn to_affine(p: G1) -> AffineG1 {
+    AffineG1::from_jacobian(p).unwrap_or_default()
+}
+
+// Prepare the inputs for the Groth16 verification by combining the public inputs with the corresponding elements of the verification key.
+// There must be one public input per IC point after the first.
+fn prepare_inputs(vk: &Groth16VerifyingKey, public_inputs: &[Fr]) -> G1 {
+    public_inputs
         .iter()
         .zip(vk.g1.k.iter().skip(1))
         .fold(vk.g1.k[0], |acc, (i, b)| acc + (*b * *i))
-        .into())
+        .into()
+}
+
+// Hash each BSB22 commitment together with the public inputs it commits to, yielding the
+// extra public inputs gnark appends to the witness.
+fn hash_commitments<F: HashToField>(
+    vk: &Groth16VerifyingKey,
+    proof: &Groth16Proof,
+    public_inputs: &[Fr],
+) -> Result<Vec<Fr>, Error> {
+    let mut hash_to_field = F::new(GROTH16_COMMITMENT_DST)?;
+    let mut hashes = Vec::with_capacity(proof.commitments.len());
+
+    for (commitment, committed) in proof
+        .commitments
+        .iter()
+        .zip(vk.public_and_commitment_committed.iter())
+    {
+        hash_to_field.write(&g1_to_bytes(commitment)?);
+        for index in committed {
+            // Committed indexes count the constant wire, public inputs start at 1. As in gnark,
+            // the public witness is extended with each commitment hash, so indexes past the
+            // public inputs refer to the hashes of the earlier commitments.
+            let public_input = index
+                .checked_sub(1)
+                .and_then(|i| {
+                    let i = i as usize;
+                    public_inputs
+                        .get(i)
+                        .or_else(|| hashes.get(i - public_inputs.len()))
+                })
+                .ok_or(VkError::CommittedIndexOutOfRange {
+                    index: *index,
+                    nb_public_inputs: public_inputs.len() + hashes.len(),
+                })?;
+            hash_to_field.write(&public_input.into_u256().to_bytes_be());
+        }
+        hashes.push(hash_to_field.sum()?);
+        hash_to_field.reset();
+    }
+
+    Ok(hashes)
+}
+
+// The Pedersen proof of knowledge of the commitments of a proof, which holds when
+// e(pok, G) * ∏ e(Cᵢ, Gσ⁻¹ᵢ) == 1. The commitments are already scaled by the powers rⁱ of the
+// challenge r derived from the hashed commitments, so all of them are checked at once.
+struct CommitmentPok {
+    pok: G1,
+    commitments: Vec<G1>,
+}
+
+impl CommitmentPok {
+    fn new(
+        vk: &Groth16VerifyingKey,
+        proof: &Groth16Proof,
+        hashed_commitments: &[Fr],
+    ) -> Result<Self, Error> {
+        let mut commitments_serialized = Vec::with_capacity(hashed_commitments.len() * 32);
+        for hashed_cmt in hashed_commitments {
+            commitments_serialized.extend_from_slice(&hashed_cmt.into_u256().to_bytes_be());
+        }
+
+        let challenge =
+            hash_to_field(&commitments_serialized, GROTH16_COMMITMENT_CHALLENGE_DST, 1)?[0];
+
+        // All commitment keys must share the same G for the proofs to be folded
+        let g = vk.commitment_keys[0].g;
+        let mut commitments = Vec::with_capacity(proof.commitments.len());
+        let mut r = Fr::one();
+        for (commitment, key) in proof.commitments.iter().zip(vk.commitment_keys.iter()) {
+            if key.g != g {
+                return Err(VkError::DistinctCommitmentBases.into());
+            }
+            commitments.push(G1::from(*commitment) * r);
+            r *= challenge;
+        }
+
+        Ok(Self {
+            pok: proof.commitment_pok.into(),
+            commitments,
+        })
+    }
+
+    // The identity proof of knowledge of `nb_commitments` commitments, to fold others into
+    fn zero(nb_commitments: usize) -> Self {
+        Self {
+            pok: G1::zero(),
+            commitments: vec![G1::zero(); nb_commitments],
+        }
+    }
+
+    // Add the proof of knowledge `other` scaled by `coefficient`
+    fn fold(&mut self, other: &Self, coefficient: Fr) {
+        self.pok = self.pok + other.pok * coefficient;
+        for (folded, commitment) in self.commitments.iter_mut().zip(other.commitments.iter()) {
+            *folded = *folded + *commitment * coefficient;
+        }
+    }
+
+    // Pair the proof of knowledge with G, then each commitment with its Gσ⁻¹, given the lines
+    // of the prepared key
+    fn pairs<'a>(
+        &self,
+        commitment_key_lines: &'a [PrecomputedLines],
+    ) -> Vec<(AffineG1, &'a PrecomputedLines)> {
+        core::iter::once(self.pok)
+            .chain(self.commitments.iter().copied())
+            .map(to_affine)
+            .zip(commitment_key_lines.iter())
+            .collect()
+    }
+
+    fn verify(&self, commitment_key_lines: &[PrecomputedLines]) -> Result<(), Error> {
+        if !pairing_check_fixed_q(&self.pairs(commitment_key_lines)) {
+            return Err(ProofCheck::CommitmentProofOfKnowledge.into());
+        }
+
+        Ok(())
+    }
 }
 
-pub fn verify_groth16(
+// Check the BSB22 commitments of a proof and combine its public inputs, the hashed
+// commitments and the commitments themselves with the IC points of the verifying key.
+// Also returns the proof of knowledge of the commitments, if any, which is left to the caller
+// to verify.
+fn prepare_proof_inputs<F: HashToField>(
     vk: &Groth16VerifyingKey,
     proof: &Groth16Proof,
     public_inputs: &[Fr],
-) -> Result<bool, Groth16Error> {
-    let alpha_g1_beta_g2 = pairing(vk.g1.alpha.into(), vk.g2.beta.into());
-    let prepared_inputs = prepare_inputs(vk.clone(), public_inputs)?;
-
-    Ok(pairing_batch(&[
-        (proof.ar.into(), proof.bs.into()),
-        (prepared_inputs, vk.g2.gamma.into()),
-        (proof.krs.into(), -Into::<G2>::into(vk.g2.delta)),
-    ]) == alpha_g1_beta_g2)
+) -> Result<(G1, Option<CommitmentPok>), Error> {
+    let nb_commitments = vk.public_and_commitment_committed.len();
+    if vk.commitment_keys.len() != nb_commitments {
+        return Err(VkError::InvalidNumberOfCommitmentKeys {
+            expected: nb_commitments,
+            got: vk.commitment_keys.len(),
+        }
+        .into());
+    }
+    if proof.commitments.len() != nb_commitments {
+        return Err(ProofCheck::NumberOfBsb22Commitments {
+            expected: nb_commitments,
+            got: proof.commitments.len(),
+        }
+        .into());
+    }
+
+    // One IC point for the constant wire, then one per public input and per commitment hash
+    let nb_public_inputs =
+        vk.g1
+            .k
+            .len()
+            .checked_sub(nb_commitments + 1)
+            .ok_or(VkError::InvalidNumberOfIcPoints {
+                expected: nb_commitments + 1,
+                got: vk.g1.k.len(),
+            })?;
+    if public_inputs.len() != nb_public_inputs {
+        return Err(ProofCheck::NumberOfPublicInputs {
+            expected: nb_public_inputs,
+            got: public_inputs.len(),
+        }
+        .into());
+    }
+
+    // The hashed commitments are appended to the public inputs
+    let mut inputs = public_inputs.to_vec();
+    let mut pok = None;
+    if nb_commitments > 0 {
+        let hashed_commitments = hash_commitments::<F>(vk, proof, public_inputs)?;
+        pok = Some(CommitmentPok::new(vk, proof, &hashed_commitments)?);
+        inputs.extend_from_slice(&hashed_commitments);
+    }
+
+    let prepared_inputs = proof
+        .commitments
+        .iter()
+        .fold(prepare_inputs(vk, &inputs), |acc, commitment| {
+            acc + (*commitment).into()
+        });
+
+    Ok((prepared_inputs, pok))
+}
+
+pub fn verify_groth16<F: HashToField>(
+    pvk: &PreparedVerifyingKey<F>,
+    proof: &Groth16Proof,
+    public_inputs: &[Fr],
+) -> Result<(), Error> {
+    let (prepared_inputs, pok) = prepare_proof_inputs::<F>(&pvk.vk, proof, public_inputs)?;
+    if let Some(pok) = pok {
+        pok.verify(&pvk.commitment_key_lines)?;
+    }
+
+    // e(Ar, Bs) * e(-PI, -γ) * e(Krs, -δ) * e(-α, β) == 1, where e(Ar, Bs) is 1 if Bs is the
+    // identity
+    let bs_lines = precompute_lines(&proof.bs);
+    let mut pairs = vec![
+        (to_affine(-prepared_inputs), &pvk.gamma_g2_neg_lines),
+        (proof.krs, &pvk.delta_g2_neg_lines),
+        (pvk.alpha_g1_neg, &pvk.beta_g2_lines),
+    ];
+    if let Some(bs_lines) = &bs_lines {
+        pairs.push((proof.ar, bs_lines));
+    }
+    if !pairing_check_fixed_q(&pairs) {
+        return Err(ProofCheck::Pairing.into());
+    }
+
+    Ok(())
+}
+
+/// Verifies several Groth16 proofs against the same verifying key
+///
+/// Each proof equation is scaled by a coefficient rᵢ and the results are multiplied together,
+/// so the whole batch is checked with a single multi-Miller loop and final exponentiation:
+///
+/// ∏ e(rᵢ * Arᵢ, Bsᵢ) * e(-∑ rᵢ * PIᵢ, -γ) * e(∑ rᵢ * Krsᵢ, -δ) * e(-(∑ rᵢ) * α, β) == 1
+///
+/// The proofs of knowledge of the BSB22 commitments are folded into the same check with
+/// their own coefficients sᵢ, adding e(∑ sᵢ * pokᵢ, G) * ∏ⱼ e(∑ sᵢ * Cᵢⱼ, Gσ⁻¹ⱼ) to the
+/// product.
+///
+/// The coefficients rᵢ then sᵢ are the successive powers of a challenge derived by
+/// Fiat-Shamir from the proofs and their public inputs.
+pub fn verify_groth16_batch<F: HashToField>(
+    pvk: &PreparedVerifyingKey<F>,
+    proofs: &[(Groth16Proof, &[Fr])],
+) -> Result<(), Error> {
+    let challenge = derive_batch_challenge(proofs)?;
+    let mut coefficients = Vec::with_capacity(2 * proofs.len());
+    let mut r = Fr::one();
+    for _ in 0..2 * proofs.len() {
+        coefficients.push(r);
+        r *= challenge;
+    }
+
+    verify_groth16_batch_with_coefficients(pvk, proofs, &coefficients)
+}
+
+/// Verifies several Groth16 proofs against the same verifying key, with coefficients drawn
+/// from `rng` instead of derived by Fiat-Shamir.
+///
+/// See [`verify_groth16_batch`].
+#[cfg(feature = "rand")]
+pub fn verify_groth16_batch_with_rng<F: HashToField, R: rand::Rng>(
+    pvk: &PreparedVerifyingKey<F>,
+    proofs: &[(Groth16Proof, &[Fr])],
+    rng: &mut R,
+) -> Result<(), Error> {
+    // The first proof keeps a unit coefficient
+    let coefficients = (0..2 * proofs.len())
+        .map(|i| if i == 0 { Fr::one() } else { Fr::random(rng) })
+        .collect::<Vec<_>>();
+
+    verify_groth16_batch_with_coefficients(pvk, proofs, &coefficients)
+}
+
+// Derive the batch challenge from every proof and its public inputs, so that a prover cannot
+// choose the proofs after the coefficients.
+fn derive_batch_challenge(proofs: &[(Groth16Proof, &[Fr])]) -> Result<Fr, Error> {
+    let mut transcript = Transcript::<Sha256>::new(&[GROTH16_BATCH_CHALLENGE])?;
+
+    for (proof, public_inputs) in proofs {
+        transcript.bind(GROTH16_BATCH_CHALLENGE, &g1_to_bytes(&proof.ar)?)?;
+        transcript.bind(
+            GROTH16_BATCH_CHALLENGE,
+            &g2_point_to_uncompressed_bytes(&proof.bs),
+        )?;
+        transcript.bind(GROTH16_BATCH_CHALLENGE, &g1_to_bytes(&proof.krs)?)?;
+        for commitment in &proof.commitments {
+            transcript.bind(GROTH16_BATCH_CHALLENGE, &g1_to_bytes(commitment)?)?;
+        }
+        transcript.bind(
+            GROTH16_BATCH_CHALLENGE,
+            &g1_to_bytes(&proof.commitment_pok)?,
+        )?;
+        for public_input in public_inputs.iter() {
+            transcript.bind(
+                GROTH16_BATCH_CHALLENGE,
+                &public_input.into_u256().to_bytes_be(),
+            )?;
+        }
+    }
+
+    let challenge = transcript.compute_challenge(GROTH16_BATCH_CHALLENGE)?;
+    Fr::from_bytes_be_mod_order(&challenge).map_err(|e| InternalError::Field(e).into())
+}
+
+// Verify the batch with one coefficient per proof equation, then one per proof of knowledge
+// of the commitments.
+fn verify_groth16_batch_with_coefficients<F: HashToField>(
+    pvk: &PreparedVerifyingKey<F>,
+    proofs: &[(Groth16Proof, &[Fr])],
+    coefficients: &[Fr],
+) -> Result<(), Error> {
+    let vk = &pvk.vk;
+    let nb_commitments = vk.commitment_keys.len();
+    let (proof_coefficients, pok_coefficients) = coefficients.split_at(proofs.len());
+
+    let mut bs_lines = Vec::with_capacity(proofs.len());
+    let mut folded_inputs = G1::zero();
+    let mut folded_krs = G1::zero();
+    let mut folded_pok = CommitmentPok::zero(nb_commitments);
+    let mut sum_r = Fr::zero();
+
+    for (((proof, public_inputs), r), s) in proofs
+        .iter()
+        .zip(proof_coefficients.iter().copied())
+        .zip(pok_coefficients.iter().copied())
+    {
+        let (prepared_inputs, pok) = prepare_proof_inputs::<F>(vk, proof, public_inputs)?;
+        if let Some(pok) = pok {
+            folded_pok.fold(&pok, s);
+        }
+
+        if let Some(lines) = precompute_lines(&proof.bs) {
+            bs_lines.push((to_affine(G1::from(proof.ar) * r), lines));
+        }
+        folded_inputs = folded_inputs + prepared_inputs * r;
+        folded_krs = folded_krs + G1::from(proof.krs) * r;
+        sum_r += r;
+    }
+
+    if proofs.is_empty() {
+        return Ok(());
+    }
+
+    let mut pairs: Vec<_> = bs_lines.iter().map(|(ar, lines)| (*ar, lines)).collect();
+    pairs.push((to_affine(-folded_inputs), &pvk.gamma_g2_neg_lines));
+    pairs.push((to_affine(folded_krs), &pvk.delta_g2_neg_lines));
+    pairs.push((pvk.alpha_g1_neg * sum_r, &pvk.beta_g2_lines));
+    if nb_commitments > 0 {
+        pairs.extend(folded_pok.pairs(&pvk.commitment_key_lines));
+    }
+
+    if !pairing_check_fixed_q(&pairs) {
+        return Err(ProofCheck::Pairing.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::g2_identity;
+    use crate::test_utils::{
+        fr, g1, groth16_commitment_proof, groth16_commitment_vk, groth16_nested_commitment_proof,
+        groth16_nested_commitment_vk, groth16_proof, groth16_vk,
+    };
+
+    fn public_inputs() -> [Fr; 2] {
+        [fr(3), fr(4)]
+    }
+
+    fn tamper_krs(proof: &mut Groth16Proof) {
+        proof.krs = (G1::from(proof.krs) + G1::one()).into();
+    }
+
+    fn validate_with(edit: impl FnOnce(&mut Groth16VerifyingKey)) -> Result<(), Error> {
+        let mut vk = groth16_commitment_vk();
+        edit(&mut vk);
+        vk.validate()
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(groth16_vk().validate().is_ok());
+        assert!(groth16_commitment_vk().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_identity_points() {
+        assert!(matches!(
+            validate_with(|vk| vk.g1.alpha = AffineG1::default()),
+            Err(Error::InvalidVerifyingKey(VkError::IdentityPoint(
+                "alpha_g1"
+            )))
+        ));
+        assert!(matches!(
+            validate_with(|vk| vk.g2.gamma = g2_identity()),
+            Err(Error::InvalidVerifyingKey(VkError::IdentityPoint(
+                "gamma_g2"
+            )))
+        ));
+        assert!(matches!(
+            validate_with(|vk| vk.g1.k[1] = AffineG1::default()),
+            Err(Error::InvalidVerifyingKey(VkError::IdentityIcPoint(1)))
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_degenerate_points() {
+        assert!(matches!(
+            validate_with(|vk| vk.g2.gamma = vk.g2.delta),
+            Err(Error::InvalidVerifyingKey(VkError::GammaEqualsDelta))
+        ));
+        assert!(matches!(
+            validate_with(|vk| vk.g1.k[2] = vk.g1.k[0]),
+            Err(Error::InvalidVerifyingKey(VkError::DuplicateIcPoint(2)))
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_inconsistent_commitments() {
+        assert!(matches!(
+            validate_with(|vk| vk.commitment_keys.clear()),
+            Err(Error::InvalidVerifyingKey(
+                VkError::InvalidNumberOfCommitmentKeys {
+                    expected: 1,
+                    got: 0,
+                }
+            ))
+        ));
+        assert!(matches!(
+            validate_with(|vk| vk.g1.k.truncate(1)),
+            Err(Error::InvalidVerifyingKey(
+                VkError::InvalidNumberOfIcPoints {
+                    expected: 2,
+                    got: 1,
+                }
+            ))
+        ));
+        for index in [0, 3] {
+            assert!(matches!(
+                validate_with(|vk| vk.public_and_commitment_committed = vec![vec![index]]),
+                Err(Error::InvalidVerifyingKey(VkError::CommittedIndexOutOfRange {
+                    index: i,
+                    nb_public_inputs: 2,
+                })) if i == index
+            ));
+        }
+
+        // The second commitment may commit to the hash of the first, but not to its own
+        assert!(groth16_nested_commitment_vk().validate().is_ok());
+        let mut vk = groth16_nested_commitment_vk();
+        vk.public_and_commitment_committed[1] = vec![4];
+        assert!(matches!(
+            vk.validate(),
+            Err(Error::InvalidVerifyingKey(
+                VkError::CommittedIndexOutOfRange {
+                    index: 4,
+                    nb_public_inputs: 3,
+                }
+            ))
+        ));
+        let mut vk = groth16_nested_commitment_vk();
+        vk.public_and_commitment_committed[0] = vec![3];
+        assert!(matches!(
+            vk.validate(),
+            Err(Error::InvalidVerifyingKey(
+                VkError::CommittedIndexOutOfRange {
+                    index: 3,
+                    nb_public_inputs: 2,
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_prepare_verifying_key() {
+        let vk = groth16_commitment_vk();
+        let pvk = prepare_verifying_key::<WrappedHashToField>(vk.clone()).unwrap();
+        assert!(pvk.alpha_g1_neg == -vk.alpha_g1());
+        assert!(Some(pvk.beta_g2_lines) == precompute_lines(&-vk.beta_g2()));
+        assert!(Some(pvk.gamma_g2_neg_lines) == precompute_lines(&-vk.gamma_g2()));
+        assert!(Some(pvk.delta_g2_neg_lines) == precompute_lines(&-vk.delta_g2()));
+        let key = &vk.commitment_keys()[0];
+        assert!(
+            pvk.commitment_key_lines
+                == [
+                    precompute_lines(&key.g()).unwrap(),
+                    precompute_lines(&key.g_root_sigma_neg()).unwrap(),
+                ]
+        );
+        let pvk = prepare_verifying_key::<WrappedHashToField>(groth16_vk()).unwrap();
+        assert!(pvk.commitment_key_lines.is_empty());
+
+        // The identity has no lines
+        let mut vk = groth16_vk();
+        vk.g2.gamma = g2_identity();
+        assert!(matches!(
+            prepare_verifying_key::<WrappedHashToField>(vk),
+            Err(Error::InvalidVerifyingKey(VkError::IdentityPoint(
+                "gamma_g2"
+            )))
+        ));
+    }
+
+    #[test]
+    fn test_verify_groth16() {
+        let pvk = prepare_verifying_key::<WrappedHashToField>(groth16_vk()).unwrap();
+        let proof = groth16_proof(&public_inputs(), fr(31), fr(37));
+        assert!(verify_groth16(&pvk, &proof, &public_inputs()).is_ok());
+
+        let mut tampered = proof.clone();
+        tamper_krs(&mut tampered);
+        assert!(matches!(
+            verify_groth16(&pvk, &tampered, &public_inputs()),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+        assert!(matches!(
+            verify_groth16(&pvk, &proof, &[fr(3), fr(5)]),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+
+    #[test]
+    fn test_verify_groth16_with_commitment() {
+        let pvk = prepare_verifying_key::<WrappedHashToField>(groth16_commitment_vk()).unwrap();
+        let proof = groth16_commitment_proof(&public_inputs(), fr(31), fr(37), fr(41));
+        assert!(verify_groth16(&pvk, &proof, &public_inputs()).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.commitment_pok = g1(fr(43));
+        assert!(matches!(
+            verify_groth16(&pvk, &tampered, &public_inputs()),
+            Err(Error::ProofRejected(ProofCheck::CommitmentProofOfKnowledge))
+        ));
+    }
+
+    #[test]
+    fn test_verify_groth16_with_nested_commitment() {
+        let pvk =
+            prepare_verifying_key::<WrappedHashToField>(groth16_nested_commitment_vk()).unwrap();
+        let proof =
+            groth16_nested_commitment_proof(&public_inputs(), fr(31), fr(37), [fr(41), fr(43)]);
+        assert!(verify_groth16(&pvk, &proof, &public_inputs()).is_ok());
+
+        // Changing the first commitment changes the hash the second one commits to
+        let mut tampered = proof.clone();
+        tampered.commitments[0] = g1(fr(47));
+        assert!(verify_groth16(&pvk, &tampered, &public_inputs()).is_err());
+
+        // The second commitment may only commit to the hash of an earlier one
+        let mut vk = groth16_nested_commitment_vk();
+        vk.public_and_commitment_committed[1] = vec![4];
+        let pvk = prepare_verifying_key::<WrappedHashToField>(vk).unwrap();
+        assert!(matches!(
+            verify_groth16(&pvk, &proof, &public_inputs()),
+            Err(Error::InvalidVerifyingKey(
+                VkError::CommittedIndexOutOfRange {
+                    index: 4,
+                    nb_public_inputs: 3,
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_verify_groth16_batch() {
+        let pvk = prepare_verifying_key::<WrappedHashToField>(groth16_vk()).unwrap();
+        let inputs = [public_inputs(), [fr(5), fr(6)], [fr(7), fr(8)]];
+        let mut proofs: Vec<(Groth16Proof, &[Fr])> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, inputs)| {
+                let i = i as u64;
+                (
+                    groth16_proof(inputs, fr(31 + i), fr(37 + i)),
+                    inputs.as_slice(),
+                )
+            })
+            .collect();
+        assert!(verify_groth16_batch(&pvk, &proofs).is_ok());
+        assert!(verify_groth16_batch(&pvk, &[]).is_ok());
+
+        tamper_krs(&mut proofs[1].0);
+        assert!(matches!(
+            verify_groth16_batch(&pvk, &proofs),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+
+    // Proofs whose errors cancel out in a sum are still rejected
+    #[test]
+    fn test_verify_groth16_batch_rejects_cancelling_proofs() {
+        let pvk = prepare_verifying_key::<WrappedHashToField>(groth16_vk()).unwrap();
+        let inputs = public_inputs();
+        let mut first = groth16_proof(&inputs, fr(31), fr(37));
+        let mut second = groth16_proof(&inputs, fr(31), fr(37));
+        first.krs = (G1::from(first.krs) + G1::one()).into();
+        second.krs = (G1::from(second.krs) - G1::one()).into();
+
+        assert!(matches!(
+            verify_groth16_batch(&pvk, &[(first, &inputs), (second, &inputs)]),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+
+    #[test]
+    fn test_verify_groth16_batch_with_commitment() {
+        let pvk = prepare_verifying_key::<WrappedHashToField>(groth16_commitment_vk()).unwrap();
+        let inputs = [public_inputs(), [fr(5), fr(6)]];
+        let mut proofs: Vec<(Groth16Proof, &[Fr])> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, inputs)| {
+                let i = i as u64;
+                (
+                    groth16_commitment_proof(inputs, fr(31 + i), fr(37 + i), fr(41 + i)),
+                    inputs.as_slice(),
+                )
+            })
+            .collect();
+        assert!(verify_groth16_batch(&pvk, &proofs).is_ok());
+
+        proofs[1].0.commitment_pok = g1(fr(43));
+        assert!(matches!(
+            verify_groth16_batch(&pvk, &proofs),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_verify_groth16_batch_with_rng() {
+        let pvk = prepare_verifying_key::<WrappedHashToField>(groth16_commitment_vk()).unwrap();
+        let inputs = public_inputs();
+        let proof = groth16_commitment_proof(&inputs, fr(31), fr(37), fr(41));
+        let mut rng = rand::thread_rng();
+        assert!(verify_groth16_batch_with_rng(
+            &pvk,
+            &[(proof.clone(), &inputs), (proof.clone(), &inputs)],
+            &mut rng
+        )
+        .is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.commitment_pok = g1(fr(43));
+        assert!(matches!(
+            verify_groth16_batch_with_rng(&pvk, &[(proof, &inputs), (tampered, &inputs)], &mut rng),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
 }
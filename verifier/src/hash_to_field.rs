@@ -1,122 +1,355 @@
+//! Hashing to the BN254 scalar field with `expand_message_xmd` from RFC 9380, compatible with
+//! gnark-crypto's `fr.Hash`.
+
 use alloc::vec;
 use alloc::vec::Vec;
-use core::hash::Hasher;
-use sha2::Digest;
+use bn::Fr;
+use core::{fmt, marker::PhantomData};
+use sha2::{
+    digest::{consts::U32, core_api::BlockSizeUser, FixedOutputReset, OutputSizeUser},
+    Digest, Sha256,
+};
+
+use crate::error::{ArgumentError, Error, InternalError};
 
-use crate::error::Error;
+/// A hash function the Fiat-Shamir challenges and the hashes to the scalar field can be
+/// computed with.
+///
+/// It is implemented for every hash of the [`digest`](sha2::digest) crate with 32-byte
+/// digests, such as [`Sha256`] (gnark's default), [`Keccak256`](sha3::Keccak256) or
+/// [`MiMC`](crate::MiMC).
+pub trait FiatShamirHash:
+    Digest + OutputSizeUser<OutputSize = U32> + BlockSizeUser + FixedOutputReset + Clone + fmt::Debug
+{
+}
+
+impl<H> FiatShamirHash for H where
+    H: Digest
+        + OutputSizeUser<OutputSize = U32>
+        + BlockSizeUser
+        + FixedOutputReset
+        + Clone
+        + fmt::Debug
+{
+}
 
-#[allow(dead_code)]
-pub(crate) struct WrappedHashToField {
+/// A hash from bytes to a scalar field element, as set with gnark's `HashToFieldFn` option.
+pub trait HashToField: Clone + fmt::Debug {
+    /// Creates a new instance with a domain separator.
+    fn new(domain_separator: &[u8]) -> Result<Self, Error>;
+
+    /// Appends bytes to the message to hash.
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Hashes the message to a field element.
+    fn sum(&self) -> Result<Fr, Error>;
+
+    /// Clears the message to hash.
+    fn reset(&mut self);
+}
+
+/// gnark's default hash to field: `expand_message_xmd` from RFC 9380 over `H`, with the
+/// domain separator as DST.
+pub struct WrappedHashToField<H = Sha256> {
     domain: Vec<u8>,
     to_hash: Vec<u8>,
+    hash: PhantomData<H>,
+}
+
+impl<H> Clone for WrappedHashToField<H> {
+    fn clone(&self) -> Self {
+        Self {
+            domain: self.domain.clone(),
+            to_hash: self.to_hash.clone(),
+            hash: PhantomData,
+        }
+    }
+}
+
+impl<H> fmt::Debug for WrappedHashToField<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WrappedHashToField")
+            .field("domain", &self.domain)
+            .finish_non_exhaustive()
+    }
 }
 
-impl WrappedHashToField {
-    // Creates a new instance with a domain separator
-    pub(crate) fn new(domain_separator: &[u8]) -> Result<Self, Error> {
+impl<H: FiatShamirHash> HashToField for WrappedHashToField<H> {
+    fn new(domain_separator: &[u8]) -> Result<Self, Error> {
         Ok(Self {
             domain: domain_separator.to_vec(),
             to_hash: Vec::new(),
+            hash: PhantomData,
         })
     }
 
-    // Hashes the bytes to a field element and returns the byte representation
-    pub(crate) fn sum(&self) -> Result<Vec<u8>, Error> {
-        let res = Self::hash(self.to_hash.clone(), self.domain.clone(), 1)?;
+    fn write(&mut self, bytes: &[u8]) {
+        self.to_hash.extend_from_slice(bytes);
+    }
+
+    fn sum(&self) -> Result<Fr, Error> {
+        let res = hash_to_field_with::<H>(&self.to_hash, &self.domain, 1)?;
 
-        Ok(res[0].clone())
+        Ok(res[0])
     }
 
-    pub fn hash(msg: Vec<u8>, dst: Vec<u8>, count: usize) -> Result<Vec<Vec<u8>>, Error> {
-        let bytes = 32;
-        let l = 16 + bytes;
+    fn reset(&mut self) {
+        self.to_hash.clear();
+    }
+}
+
+/// Hashes `msg` to `count` elements of the scalar field with SHA-256, as gnark-crypto's
+/// `fr.Hash`.
+///
+/// Each element is read from 48 bytes of `expand_message_xmd(msg, dst, 48 * count)` and reduced
+/// modulo the field order, following `hash_to_field` from RFC 9380 with L = 48.
+///
+/// # Errors
+///
+/// Returns an error if `dst` is longer than 255 bytes or `count` is larger than 170.
+pub fn hash_to_field(msg: &[u8], dst: &[u8], count: usize) -> Result<Vec<Fr>, Error> {
+    hash_to_field_with::<Sha256>(msg, dst, count)
+}
 
-        let len_in_bytes = count * l;
-        let pseudo_random_bytes = Self::expand_msg_xmd(msg, dst, len_in_bytes).unwrap();
+/// Hashes `msg` to `count` elements of the scalar field like [`hash_to_field`], with `H` in
+/// place of SHA-256.
+pub fn hash_to_field_with<H: FiatShamirHash>(
+    msg: &[u8],
+    dst: &[u8],
+    count: usize,
+) -> Result<Vec<Fr>, Error> {
+    // L = ceil((ceil(log2(r)) + k) / 8) with k = 128 bits of security
+    let l = 16 + 32;
 
-        let mut res = Vec::new();
-        for i in 0..count {
-            res.push(pseudo_random_bytes[i * l..(i + 1) * l].to_vec());
-        }
+    let pseudo_random_bytes = expand_msg_xmd::<H>(msg, dst, count * l)?;
+
+    pseudo_random_bytes
+        .chunks(l)
+        .map(|bytes| Fr::from_bytes_be_mod_order(bytes).map_err(|e| InternalError::Field(e).into()))
+        .collect()
+}
 
-        Ok(res)
+/// Expands `msg` to `len` pseudo-random bytes with `expand_message_xmd` from RFC 9380 over `H`.
+///
+/// # Errors
+///
+/// Returns an error if `dst` is longer than 255 bytes or `len` needs more than 255 blocks of
+/// `H`.
+pub fn expand_msg_xmd<H: FiatShamirHash>(
+    msg: &[u8],
+    dst: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut h = H::new();
+
+    // The output and input block sizes of the hash, b_in_bytes and s_in_bytes in RFC 9380
+    let b_in_bytes = <H as Digest>::output_size();
+    let s_in_bytes = H::block_size();
+
+    let ell = len.div_ceil(b_in_bytes);
+
+    if ell > 255 {
+        Err(ArgumentError::EllTooLarge)?;
+    }
+    if dst.len() > 255 {
+        Err(ArgumentError::DSTTooLarge)?;
     }
 
-    fn expand_msg_xmd(msg: Vec<u8>, dst: Vec<u8>, len: usize) -> Result<Vec<u8>, Error> {
-        let mut h = sha2::Sha256::new();
+    let size_domain = dst.len();
 
-        let ell = (len + 32 - 1) / 32;
+    Digest::reset(&mut h);
 
-        if ell > 255 {
-            Err(Error::EllTooLarge)?;
-        }
-        if dst.len() > 255 {
-            Err(Error::DSTTooLarge)?;
-        }
+    // b_0 = H(Z_pad || msg || l_i_b_str || I2OSP(0, 1) || DST_prime)
+    Digest::update(&mut h, vec![0u8; s_in_bytes]);
+    Digest::update(&mut h, msg);
+    Digest::update(&mut h, [(len >> 8) as u8, len as u8, 0]);
+    Digest::update(&mut h, dst);
+    Digest::update(&mut h, [size_domain as u8]);
+    let b0 = Digest::finalize_reset(&mut h);
+
+    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
+    Digest::update(&mut h, b0);
+    Digest::update(&mut h, [1]); // I2OSP(1, 1)
+    Digest::update(&mut h, dst);
+    Digest::update(&mut h, [size_domain as u8]);
+    let mut b1 = Digest::finalize_reset(&mut h);
 
-        let size_domain = dst.len();
-
-        h.reset();
-
-        // b_0 = H(msg_prime)
-        h.update([0u8; 64]); // Assuming the block size is 64 bytes for SHA-256
-        h.update(&msg);
-        h.update([(len >> 8) as u8, len as u8, 0]);
-        h.update(&dst);
-        h.update([size_domain as u8]);
-        let b0 = h.finalize_reset();
-
-        // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
-        h.update(b0);
-        h.update([1]); // I2OSP(1, 1)
-        h.update(&dst);
-        h.update([size_domain as u8]);
-        let mut b1 = h.finalize_reset();
-
-        let mut res = vec![0u8; len];
-        res[..32].copy_from_slice(&b1);
-
-        for i in 2..=ell {
-            h.reset();
-            let mut strxor = vec![0u8; 32];
-            for (j, (b0_byte, b1_byte)) in b0.iter().zip(b1.iter()).enumerate() {
-                strxor[j] = b0_byte ^ b1_byte;
-            }
-            h.update(&strxor);
-            h.update([i as u8]);
-            h.update(&dst);
-            h.update([size_domain as u8]);
-            b1 = h.finalize_reset();
-
-            let start = 32 * (i - 1);
-            let end = core::cmp::min(start + 32, res.len());
-            res[start..end].copy_from_slice(&b1[..end - start]);
+    let mut res = vec![0u8; len];
+    let end = core::cmp::min(b_in_bytes, len);
+    res[..end].copy_from_slice(&b1[..end]);
+
+    for i in 2..=ell {
+        Digest::reset(&mut h);
+        let mut strxor = vec![0u8; b_in_bytes];
+        for (j, (b0_byte, b1_byte)) in b0.iter().zip(b1.iter()).enumerate() {
+            strxor[j] = b0_byte ^ b1_byte;
         }
+        Digest::update(&mut h, &strxor);
+        Digest::update(&mut h, [i as u8]);
+        Digest::update(&mut h, dst);
+        Digest::update(&mut h, [size_domain as u8]);
+        b1 = Digest::finalize_reset(&mut h);
 
-        Ok(res)
+        let start = b_in_bytes * (i - 1);
+        let end = core::cmp::min(start + b_in_bytes, res.len());
+        res[start..end].copy_from_slice(&b1[..end - start]);
     }
+
+    Ok(res)
 }
 
-impl Hasher for WrappedHashToField {
-    fn finish(&self) -> u64 {
-        // This method is not directly applicable to field elements, so it's a stub
-        unimplemented!();
+/// A hash to field that hashes the message with `H` alone and ignores the domain separator,
+/// as gnark does when a plain hash such as Keccak-256 is passed as `HashToFieldFn`.
+///
+/// The digest is reduced modulo the field order.
+pub struct RawHashToField<H = Sha256> {
+    h: H,
+}
+
+impl<H: Clone> Clone for RawHashToField<H> {
+    fn clone(&self) -> Self {
+        Self { h: self.h.clone() }
     }
+}
 
-    fn write(&mut self, bytes: &[u8]) {
-        self.to_hash.extend_from_slice(bytes);
+impl<H: fmt::Debug> fmt::Debug for RawHashToField<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawHashToField")
+            .field("h", &self.h)
+            .finish()
     }
 }
 
-impl Default for WrappedHashToField {
-    fn default() -> Self {
-        Self::new(&[]).unwrap()
+impl<H: FiatShamirHash> HashToField for RawHashToField<H> {
+    fn new(_domain_separator: &[u8]) -> Result<Self, Error> {
+        Ok(Self { h: H::new() })
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        Digest::update(&mut self.h, bytes);
+    }
+
+    fn sum(&self) -> Result<Fr, Error> {
+        let res = self.h.clone().finalize();
+
+        Fr::from_bytes_be_mod_order(&res).map_err(|e| InternalError::Field(e).into())
+    }
+
+    fn reset(&mut self) {
+        Digest::reset(&mut self.h);
     }
 }
 
-impl WrappedHashToField {
-    // Resets the state of the hasher
-    pub(crate) fn reset(&mut self) {
-        self.to_hash.clear();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 9380, appendix K.1: expand_message_xmd(SHA-256)
+    const RFC_9380_DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA256-128";
+
+    fn rfc_9380_vectors() -> Vec<(Vec<u8>, usize, &'static str)> {
+        let q128 = [b"q128_".as_slice(), &[b'q'; 128]].concat();
+        let a512 = [b"a512_".as_slice(), &[b'a'; 512]].concat();
+
+        vec![
+            (
+                b"".to_vec(),
+                0x20,
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                b"abc".to_vec(),
+                0x20,
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+            (
+                b"abcdef0123456789".to_vec(),
+                0x20,
+                "eff31487c770a893cfb36f912fbfcbff40d5661771ca4b2cb4eafe524333f5c1",
+            ),
+            (
+                q128,
+                0x20,
+                "b23a1d2b4d97b2ef7785562a7e8bac7eed54ed6e97e29aa51bfe3f12ddad1ff9",
+            ),
+            (
+                a512,
+                0x20,
+                "4623227bcc01293b8c130bf771da8c298dede7383243dc0993d2d94823958c4c",
+            ),
+            (
+                b"".to_vec(),
+                0x80,
+                "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbe\
+                 e0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18\
+                 eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dc\
+                 c541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced",
+            ),
+            (
+                b"abc".to_vec(),
+                0x80,
+                "abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a\
+                 647e6c3163d40b76a73cf6a5674ef1d890f95b664ee0afa5359a5c4e07985635\
+                 bbecbac65d747d3d2da7ec2b8221b17b0ca9dc8a1ac1c07ea6a1e60583e2cb00\
+                 058e77b7b72a298425cd1b941ad4ec65e8afc50303a22c0f99b0509b4c895f40",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_expand_msg_xmd_rfc_9380_vectors() {
+        for (msg, len, expected) in rfc_9380_vectors() {
+            let uniform_bytes = expand_msg_xmd::<Sha256>(&msg, RFC_9380_DST, len).unwrap();
+            assert_eq!(hex::encode(uniform_bytes), expected);
+        }
+    }
+
+    #[test]
+    fn test_expand_msg_xmd_rejects_large_arguments() {
+        assert!(matches!(
+            expand_msg_xmd::<Sha256>(b"", &[0; 256], 32),
+            Err(Error::InvalidArgument(ArgumentError::DSTTooLarge))
+        ));
+        assert!(matches!(
+            expand_msg_xmd::<Sha256>(b"", RFC_9380_DST, 255 * 32 + 1),
+            Err(Error::InvalidArgument(ArgumentError::EllTooLarge))
+        ));
+    }
+
+    // The hash of the uncompressed G1 generator with the DST of gnark's Groth16 BSB22
+    // commitments, the first element being what `fr.Hash` returns for one element
+    #[test]
+    fn test_hash_to_field_bsb22_commitment() {
+        let mut msg = vec![0u8; 64];
+        msg[31] = 1;
+        msg[63] = 2;
+
+        let expected = [
+            "12633374015079125923182969818453856482727084099299750220579204379839331225415",
+            "234884679878174281712635502357155599682866874930966289526419965365692981049",
+        ]
+        .map(|e| Fr::from_str(e).unwrap());
+        assert_eq!(
+            hash_to_field(&msg, b"bsb22-commitment", 2).unwrap(),
+            expected
+        );
+
+        let single = Fr::from_str(
+            "19930680429654425682544533999854559281333984511452325342822469879950091967287",
+        )
+        .unwrap();
+        assert_eq!(
+            hash_to_field(&msg, b"bsb22-commitment", 1).unwrap(),
+            [single]
+        );
+
+        let mut hasher = WrappedHashToField::<Sha256>::new(b"bsb22-commitment").unwrap();
+        hasher.write(&msg[..32]);
+        hasher.write(&msg[32..]);
+        assert_eq!(hasher.sum().unwrap(), single);
+
+        hasher.reset();
+        hasher.write(b"another message");
+        assert_ne!(hasher.sum().unwrap(), single);
     }
 }
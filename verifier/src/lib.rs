@@ -6,23 +6,46 @@
 #![no_std]
 extern crate alloc;
 
+use alloc::vec::Vec;
 use bn::Fr;
+#[cfg(feature = "rand")]
+use groth16::verify_groth16_batch_with_rng;
 use groth16::{
-    error::Groth16Error, load_groth16_proof_from_bytes, load_groth16_verifying_key_from_bytes,
-    verify_groth16,
+    load_groth16_proof_from_bytes, load_groth16_verifying_key_from_bytes, prepare_verifying_key,
+    verify_groth16, verify_groth16_batch,
 };
 use plonk::{
-    error::PlonkError, load_plonk_proof_from_bytes, load_plonk_verifying_key_from_bytes,
-    verify_plonk,
+    load_plonk_proof_from_bytes, load_plonk_verifying_key_from_bytes, prepare_plonk_verifying_key,
+    verify_plonk, verify_plonk_batch,
 };
+use reader::ParseMode;
+use sp1::{embedded_vk, sp1_public_inputs};
 
 mod constants;
 mod converter;
 mod error;
 mod groth16;
-mod hash_to_field;
+pub mod hash_to_field;
+mod mimc;
+mod pairing;
 mod plonk;
+mod reader;
+mod sp1;
+#[cfg(test)]
+mod test_utils;
 mod transcript;
+mod writer;
+
+pub use converter::PointEncoding;
+pub use error::{ArgumentError, DecodeError, Error, InternalError, ProofCheck, VkError};
+pub use groth16::{Groth16Proof, Groth16VerifyingKey, PedersenVerifyingKey, PreparedVerifyingKey};
+pub use hash_to_field::{FiatShamirHash, HashToField, RawHashToField, WrappedHashToField};
+pub use mimc::MiMC;
+pub use plonk::{kzg, GnarkVersion, PlonkProof, PlonkVerifyingKey, PreparedPlonkVerifyingKey};
+pub use reader::VkValidation;
+pub use sha2::Sha256;
+pub use sha3::Keccak256;
+pub use sp1::{PublicValuesHash, Sp1Circuit, Sp1Error, Sp1ProofSystem};
 
 /// A verifier for Groth16 zero-knowledge proofs.
 #[derive(Debug)]
@@ -39,13 +62,173 @@ impl Groth16Verifier {
     ///
     /// # Returns
     ///
-    /// A `Result` containing a boolean indicating whether the proof is valid,
-    /// or a `Groth16Error` if verification fails.
-    pub fn verify(proof: &[u8], vk: &[u8], public_inputs: &[Fr]) -> Result<bool, Groth16Error> {
-        let proof = load_groth16_proof_from_bytes(proof).unwrap();
-        let vk = load_groth16_verifying_key_from_bytes(vk).unwrap();
+    /// `Ok(())` if the proof is valid, or an `Error` telling whether the bytes are malformed,
+    /// the key is invalid or the proof is rejected.
+    pub fn verify(proof: &[u8], vk: &[u8], public_inputs: &[Fr]) -> Result<(), Error> {
+        let proof = load_groth16_proof_from_bytes(proof, ParseMode::Lenient)?;
+        let vk =
+            load_groth16_verifying_key_from_bytes(vk, ParseMode::Lenient, VkValidation::Trusted)?;
+
+        verify_groth16(
+            &prepare_verifying_key::<WrappedHashToField>(vk)?,
+            &proof,
+            public_inputs,
+        )
+    }
+
+    /// Verifies a Groth16 proof, rejecting proof or verification key bytes that contain
+    /// anything after the serialized object.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof bytes.
+    /// * `vk` - The verification key bytes.
+    /// * `public_inputs` - The public inputs.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the proof is valid, or an `Error` telling whether the bytes are malformed,
+    /// the key is invalid or the proof is rejected.
+    pub fn verify_strict(proof: &[u8], vk: &[u8], public_inputs: &[Fr]) -> Result<(), Error> {
+        let proof = load_groth16_proof_from_bytes(proof, ParseMode::Strict)?;
+        let vk =
+            load_groth16_verifying_key_from_bytes(vk, ParseMode::Strict, VkValidation::Trusted)?;
+
+        verify_groth16(
+            &prepare_verifying_key::<WrappedHashToField>(vk)?,
+            &proof,
+            public_inputs,
+        )
+    }
+
+    /// Parses a verification key and precomputes the parts of the pairing check that do not
+    /// depend on the proof.
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The verification key bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `PreparedVerifyingKey`, or an `Error` if the key
+    /// cannot be parsed or one of its G2 points is the identity.
+    pub fn prepare(vk: &[u8]) -> Result<PreparedVerifyingKey, Error> {
+        Self::prepare_with_hash_to_field(vk)
+    }
+
+    /// Parses a verification key like [`Groth16Verifier::prepare`], for proofs whose BSB22
+    /// commitments were hashed with `F` instead of gnark's default hash to field, such as
+    /// `RawHashToField<Keccak256>` for circuits targeting Solidity.
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The verification key bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `PreparedVerifyingKey`, or an `Error` if the key
+    /// cannot be parsed or one of its G2 points is the identity.
+    pub fn prepare_with_hash_to_field<F: HashToField>(
+        vk: &[u8],
+    ) -> Result<PreparedVerifyingKey<F>, Error> {
+        Self::prepare_with_validation(vk, VkValidation::Trusted)
+    }
+
+    /// Parses a verification key like [`Groth16Verifier::prepare_with_hash_to_field`],
+    /// checking its points as set by `validation`.
+    ///
+    /// Keys from untrusted sources should be loaded with [`VkValidation::Full`].
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The verification key bytes.
+    /// * `validation` - The checks to run on the points of the key.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `PreparedVerifyingKey`, or an `Error` if the key
+    /// cannot be parsed or one of its points is invalid.
+    pub fn prepare_with_validation<F: HashToField>(
+        vk: &[u8],
+        validation: VkValidation,
+    ) -> Result<PreparedVerifyingKey<F>, Error> {
+        let vk = load_groth16_verifying_key_from_bytes(vk, ParseMode::Lenient, validation)?;
+
+        prepare_verifying_key(vk)
+    }
+
+    /// Verifies a Groth16 proof against a prepared verification key.
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The prepared verification key, see [`Groth16Verifier::prepare`].
+    /// * `proof` - The proof bytes.
+    /// * `public_inputs` - The public inputs.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the proof is valid, or an `Error` telling whether the bytes are malformed,
+    /// the key is invalid or the proof is rejected.
+    pub fn verify_prepared<F: HashToField>(
+        vk: &PreparedVerifyingKey<F>,
+        proof: &[u8],
+        public_inputs: &[Fr],
+    ) -> Result<(), Error> {
+        let proof = load_groth16_proof_from_bytes(proof, ParseMode::Lenient)?;
 
-        verify_groth16(&vk, &proof, public_inputs)
+        verify_groth16(vk, &proof, public_inputs)
+    }
+
+    /// Verifies a batch of Groth16 proofs that share the same verification key.
+    ///
+    /// The proofs are combined with coefficients derived by Fiat-Shamir from the proofs and
+    /// their public inputs, so that the whole batch costs a single multi-Miller loop and final
+    /// exponentiation.
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The verification key bytes.
+    /// * `proofs` - The proof bytes, each paired with its public inputs.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if all proofs are valid, or an `Error` if any of them cannot be parsed or is
+    /// rejected.
+    pub fn verify_batch(vk: &[u8], proofs: &[(&[u8], &[Fr])]) -> Result<(), Error> {
+        let vk = Self::prepare(vk)?;
+        let proofs = Self::load_batch(proofs)?;
+
+        verify_groth16_batch(&vk, &proofs)
+    }
+
+    /// Verifies a batch of Groth16 proofs that share the same verification key, combining them
+    /// with coefficients drawn from `rng`.
+    ///
+    /// See [`Groth16Verifier::verify_batch`].
+    #[cfg(feature = "rand")]
+    pub fn verify_batch_with_rng<R: rand::Rng>(
+        vk: &[u8],
+        proofs: &[(&[u8], &[Fr])],
+        rng: &mut R,
+    ) -> Result<(), Error> {
+        let vk = Self::prepare(vk)?;
+        let proofs = Self::load_batch(proofs)?;
+
+        verify_groth16_batch_with_rng(&vk, &proofs, rng)
+    }
+
+    fn load_batch<'a>(
+        proofs: &[(&[u8], &'a [Fr])],
+    ) -> Result<Vec<(Groth16Proof, &'a [Fr])>, Error> {
+        proofs
+            .iter()
+            .map(|(proof, public_inputs)| {
+                Ok((
+                    load_groth16_proof_from_bytes(proof, ParseMode::Lenient)?,
+                    *public_inputs,
+                ))
+            })
+            .collect()
     }
 }
 
@@ -64,12 +247,493 @@ impl PlonkVerifier {
     ///
     /// # Returns
     ///
-    /// A `Result` containing a boolean indicating whether the proof is valid,
-    /// or a `PlonkError` if verification fails.
-    pub fn verify(proof: &[u8], vk: &[u8], public_inputs: &[Fr]) -> Result<bool, PlonkError> {
-        let proof = load_plonk_proof_from_bytes(proof).unwrap();
-        let vk = load_plonk_verifying_key_from_bytes(vk).unwrap();
+    /// `Ok(())` if the proof is valid, or an `Error` telling whether the bytes are malformed,
+    /// the key is invalid or the proof is rejected.
+    pub fn verify(proof: &[u8], vk: &[u8], public_inputs: &[Fr]) -> Result<(), Error> {
+        let proof = load_plonk_proof_from_bytes(proof, ParseMode::Lenient)?;
+        let vk = Self::prepare(vk)?;
 
         verify_plonk(&vk, &proof, public_inputs)
     }
+
+    /// Verifies a Plonk proof, rejecting proof or verification key bytes that contain
+    /// anything after the serialized object.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof bytes.
+    /// * `vk` - The verification key bytes.
+    /// * `public_inputs` - The public inputs.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the proof is valid, or an `Error` telling whether the bytes are malformed,
+    /// the key is invalid or the proof is rejected.
+    pub fn verify_strict(proof: &[u8], vk: &[u8], public_inputs: &[Fr]) -> Result<(), Error> {
+        let proof = load_plonk_proof_from_bytes(proof, ParseMode::Strict)?;
+        let vk = load_plonk_verifying_key_from_bytes(vk, ParseMode::Strict, VkValidation::Trusted)?;
+
+        let vk: PreparedPlonkVerifyingKey = prepare_plonk_verifying_key(vk)?;
+
+        verify_plonk(&vk, &proof, public_inputs)
+    }
+
+    /// Parses a verification key and precomputes the parts of the transcript and of the
+    /// public input evaluation that do not depend on the proof.
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The verification key bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `PreparedPlonkVerifyingKey`, or an `Error` if the key
+    /// cannot be parsed.
+    pub fn prepare(vk: &[u8]) -> Result<PreparedPlonkVerifyingKey, Error> {
+        Self::prepare_with_hashes(vk)
+    }
+
+    /// Parses a verification key like [`PlonkVerifier::prepare`], for proofs whose Fiat-Shamir
+    /// challenges were derived with `C` and whose BSB22 commitments were hashed with `F`,
+    /// instead of gnark's defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The verification key bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `PreparedPlonkVerifyingKey`, or an `Error` if the key
+    /// cannot be parsed.
+    pub fn prepare_with_hashes<C: FiatShamirHash, F: HashToField>(
+        vk: &[u8],
+    ) -> Result<PreparedPlonkVerifyingKey<C, F>, Error> {
+        Self::prepare_with_validation(vk, VkValidation::Trusted)
+    }
+
+    /// Parses a verification key like [`PlonkVerifier::prepare_with_hashes`], checking its
+    /// points as set by `validation`.
+    ///
+    /// Keys from untrusted sources should be loaded with [`VkValidation::Full`].
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The verification key bytes.
+    /// * `validation` - The checks to run on the points of the key.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `PreparedPlonkVerifyingKey`, or an `Error` if the key
+    /// cannot be parsed or one of its points is invalid.
+    pub fn prepare_with_validation<C: FiatShamirHash, F: HashToField>(
+        vk: &[u8],
+        validation: VkValidation,
+    ) -> Result<PreparedPlonkVerifyingKey<C, F>, Error> {
+        let vk = load_plonk_verifying_key_from_bytes(vk, ParseMode::Lenient, validation)?;
+
+        prepare_plonk_verifying_key(vk)
+    }
+
+    /// Verifies a Plonk proof against a prepared verification key.
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The prepared verification key, see [`PlonkVerifier::prepare`].
+    /// * `proof` - The proof bytes.
+    /// * `public_inputs` - The public inputs.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the proof is valid, or an `Error` telling whether the bytes are malformed,
+    /// the key is invalid or the proof is rejected.
+    pub fn verify_prepared<C: FiatShamirHash, F: HashToField>(
+        vk: &PreparedPlonkVerifyingKey<C, F>,
+        proof: &[u8],
+        public_inputs: &[Fr],
+    ) -> Result<(), Error> {
+        let proof = load_plonk_proof_from_bytes(proof, ParseMode::Lenient)?;
+
+        verify_plonk(vk, &proof, public_inputs)
+    }
+
+    /// Verifies a batch of Plonk proofs that share the same verification key.
+    ///
+    /// Each proof goes through its own transcript and linearization, then the KZG openings of
+    /// all the proofs are combined with coefficients derived by Fiat-Shamir, so that the whole
+    /// batch costs a single pairing check.
+    ///
+    /// # Arguments
+    ///
+    /// * `vk` - The verification key bytes.
+    /// * `proofs` - The proof bytes, each paired with its public inputs.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if all proofs are valid, or an `Error` if any of them cannot be parsed or is
+    /// rejected.
+    pub fn verify_batch(vk: &[u8], proofs: &[(&[u8], &[Fr])]) -> Result<(), Error> {
+        let vk = Self::prepare(vk)?;
+        let proofs = proofs
+            .iter()
+            .map(|(proof, public_inputs)| {
+                Ok((
+                    load_plonk_proof_from_bytes(proof, ParseMode::Lenient)?,
+                    *public_inputs,
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        verify_plonk_batch(&vk, &proofs)
+    }
+}
+
+/// A verifier for the Groth16 proofs of SP1 programs.
+///
+/// The public inputs of the SP1 Groth16 circuit are derived from the program vkey hash and
+/// the public values, instead of being passed by the caller.
+#[derive(Debug)]
+pub struct Sp1Groth16Verifier;
+
+impl Sp1Groth16Verifier {
+    /// Verifies the Groth16 proof of an SP1 program whose public values were committed with
+    /// SHA-256.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The raw gnark proof bytes.
+    /// * `public_values` - The public values committed by the program.
+    /// * `program_vkey_hash` - The program vkey hash, as a hex string.
+    /// * `vk` - The verification key bytes of the SP1 Groth16 circuit.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is `Ok(())` if the proof is valid, or an `Sp1Error` if it does not
+    /// match the program vkey hash and the public values, or cannot be processed.
+    pub fn verify(
+        proof: &[u8],
+        public_values: &[u8],
+        program_vkey_hash: &str,
+        vk: &[u8],
+    ) -> Result<(), Sp1Error> {
+        Self::verify_with_hash(
+            proof,
+            public_values,
+            program_vkey_hash,
+            vk,
+            PublicValuesHash::Sha256,
+        )
+    }
+
+    /// Verifies the Groth16 proof of an SP1 program, hashing the public values with `hash`.
+    ///
+    /// See [`Sp1Groth16Verifier::verify`].
+    pub fn verify_with_hash(
+        proof: &[u8],
+        public_values: &[u8],
+        program_vkey_hash: &str,
+        vk: &[u8],
+        hash: PublicValuesHash,
+    ) -> Result<(), Sp1Error> {
+        let public_inputs = sp1_public_inputs(public_values, program_vkey_hash, hash, &[])?;
+
+        Self::verify_public_inputs(proof, vk, &public_inputs)
+    }
+
+    /// Verifies the Groth16 proof of an SP1 program against the verifying key embedded in the
+    /// crate that its 4-byte selector points to.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof bytes, prefixed with the selector of the verifying key.
+    /// * `public_values` - The public values committed by the program.
+    /// * `program_vkey_hash` - The program vkey hash, as a hex string.
+    /// * `hash` - The hash function the public values were committed with.
+    /// * `extra_public_inputs` - The public inputs after the committed values digest, for
+    ///   circuit releases that have them, see [`Sp1Circuit::nb_public_inputs`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is `Ok(())` if the proof is valid, or an `Sp1Error` if no embedded
+    /// verifying key matches the selector, the proof does not match the program vkey hash and
+    /// the public values, or it cannot be processed.
+    pub fn verify_with_selector(
+        proof: &[u8],
+        public_values: &[u8],
+        program_vkey_hash: &str,
+        hash: PublicValuesHash,
+        extra_public_inputs: &[Fr],
+    ) -> Result<(), Sp1Error> {
+        let (circuit, vk, proof) = embedded_vk(proof, Sp1ProofSystem::Groth16)?;
+        let public_inputs =
+            sp1_public_inputs(public_values, program_vkey_hash, hash, extra_public_inputs)?;
+        if public_inputs.len() != circuit.nb_public_inputs() {
+            return Err(Sp1Error::InvalidNumberOfPublicInputs {
+                expected: circuit.nb_public_inputs(),
+                got: public_inputs.len(),
+            });
+        }
+
+        Self::verify_public_inputs(proof, vk, &public_inputs)
+    }
+
+    fn verify_public_inputs(proof: &[u8], vk: &[u8], public_inputs: &[Fr]) -> Result<(), Sp1Error> {
+        // A wrong vkey hash or digest makes the pairing or the commitment check fail
+        match Groth16Verifier::verify(proof, vk, public_inputs) {
+            Err(Error::ProofRejected(
+                ProofCheck::Pairing | ProofCheck::CommitmentProofOfKnowledge,
+            )) => Err(Sp1Error::PublicInputsMismatch),
+            result => Ok(result?),
+        }
+    }
+}
+
+/// A verifier for the Plonk proofs of SP1 programs.
+///
+/// The public inputs of the SP1 Plonk circuit are derived from the program vkey hash and the
+/// public values, instead of being passed by the caller.
+#[derive(Debug)]
+pub struct Sp1PlonkVerifier;
+
+impl Sp1PlonkVerifier {
+    /// Verifies the Plonk proof of an SP1 program whose public values were committed with
+    /// SHA-256.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The raw gnark proof bytes.
+    /// * `public_values` - The public values committed by the program.
+    /// * `program_vkey_hash` - The program vkey hash, as a hex string.
+    /// * `vk` - The verification key bytes of the SP1 Plonk circuit.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is `Ok(())` if the proof is valid, or an `Sp1Error` if it does not
+    /// match the program vkey hash and the public values, or cannot be processed.
+    pub fn verify(
+        proof: &[u8],
+        public_values: &[u8],
+        program_vkey_hash: &str,
+        vk: &[u8],
+    ) -> Result<(), Sp1Error> {
+        Self::verify_with_hash(
+            proof,
+            public_values,
+            program_vkey_hash,
+            vk,
+            PublicValuesHash::Sha256,
+        )
+    }
+
+    /// Verifies the Plonk proof of an SP1 program, hashing the public values with `hash`.
+    ///
+    /// See [`Sp1PlonkVerifier::verify`].
+    pub fn verify_with_hash(
+        proof: &[u8],
+        public_values: &[u8],
+        program_vkey_hash: &str,
+        vk: &[u8],
+        hash: PublicValuesHash,
+    ) -> Result<(), Sp1Error> {
+        let public_inputs = sp1_public_inputs(public_values, program_vkey_hash, hash, &[])?;
+
+        Self::verify_public_inputs(proof, vk, &public_inputs)
+    }
+
+    /// Verifies the Plonk proof of an SP1 program against the verifying key embedded in the
+    /// crate that its 4-byte selector points to.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof bytes, prefixed with the selector of the verifying key.
+    /// * `public_values` - The public values committed by the program.
+    /// * `program_vkey_hash` - The program vkey hash, as a hex string.
+    /// * `hash` - The hash function the public values were committed with.
+    /// * `extra_public_inputs` - The public inputs after the committed values digest, for
+    ///   circuit releases that have them, see [`Sp1Circuit::nb_public_inputs`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is `Ok(())` if the proof is valid, or an `Sp1Error` if no embedded
+    /// verifying key matches the selector, the proof does not match the program vkey hash and
+    /// the public values, or it cannot be processed.
+    pub fn verify_with_selector(
+        proof: &[u8],
+        public_values: &[u8],
+        program_vkey_hash: &str,
+        hash: PublicValuesHash,
+        extra_public_inputs: &[Fr],
+    ) -> Result<(), Sp1Error> {
+        let (circuit, vk, proof) = embedded_vk(proof, Sp1ProofSystem::Plonk)?;
+        let public_inputs =
+            sp1_public_inputs(public_values, program_vkey_hash, hash, extra_public_inputs)?;
+        if public_inputs.len() != circuit.nb_public_inputs() {
+            return Err(Sp1Error::InvalidNumberOfPublicInputs {
+                expected: circuit.nb_public_inputs(),
+                got: public_inputs.len(),
+            });
+        }
+
+        Self::verify_public_inputs(proof, vk, &public_inputs)
+    }
+
+    fn verify_public_inputs(proof: &[u8], vk: &[u8], public_inputs: &[Fr]) -> Result<(), Sp1Error> {
+        // A wrong vkey hash or digest makes one of the final opening checks fail
+        match PlonkVerifier::verify(proof, vk, public_inputs) {
+            Err(Error::ProofRejected(
+                ProofCheck::Pairing | ProofCheck::LinearizedPolynomialOpening,
+            )) => Err(Sp1Error::PublicInputsMismatch),
+            result => Ok(result?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        converter::g1_point_to_uncompressed_bytes,
+        test_utils::{fr, g1, groth16_proof, groth16_vk, off_curve_g1, plonk_vk},
+    };
+
+    // An uncompressed proof of [`groth16_vk`] is Ar, Bs and Krs, then an empty commitment list
+    // and the proof of knowledge
+    const GROTH16_KRS_OFFSET: usize = 64 + 128;
+    const GROTH16_COMMITMENTS_OFFSET: usize = GROTH16_KRS_OFFSET + 64;
+    const GROTH16_PROOF_SIZE: usize = GROTH16_COMMITMENTS_OFFSET + 4 + 64;
+
+    fn public_inputs() -> [Fr; 2] {
+        [fr(3), fr(4)]
+    }
+
+    fn groth16_bytes() -> (Vec<u8>, Vec<u8>) {
+        let proof = groth16_proof(&public_inputs(), fr(31), fr(37));
+        let vk = groth16_vk().with_encoding(PointEncoding::Uncompressed);
+        (
+            proof.to_gnark_bytes().unwrap(),
+            vk.to_gnark_bytes().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_groth16_verify() {
+        let (proof, vk) = groth16_bytes();
+        assert_eq!(proof.len(), GROTH16_PROOF_SIZE);
+        assert!(Groth16Verifier::verify(&proof, &vk, &public_inputs()).is_ok());
+        assert!(Groth16Verifier::verify_strict(&proof, &vk, &public_inputs()).is_ok());
+    }
+
+    #[test]
+    fn test_groth16_rejected_proofs() {
+        let (mut proof, vk) = groth16_bytes();
+
+        assert!(matches!(
+            Groth16Verifier::verify(&proof, &vk, &[fr(3), fr(5)]),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+        assert!(matches!(
+            Groth16Verifier::verify(&proof, &vk, &[fr(3)]),
+            Err(Error::ProofRejected(ProofCheck::NumberOfPublicInputs {
+                expected: 2,
+                got: 1,
+            }))
+        ));
+
+        // A well-formed Krs that does not satisfy the equation
+        proof[GROTH16_KRS_OFFSET..GROTH16_COMMITMENTS_OFFSET]
+            .copy_from_slice(&g1_point_to_uncompressed_bytes(&g1(fr(5))));
+        assert!(matches!(
+            Groth16Verifier::verify(&proof, &vk, &public_inputs()),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+
+    #[test]
+    fn test_groth16_malformed_proofs() {
+        let (proof, vk) = groth16_bytes();
+
+        assert!(matches!(
+            Groth16Verifier::verify(&proof[..100], &vk, &public_inputs()),
+            Err(Error::MalformedInput {
+                offset: 64,
+                field: "bs",
+                kind: DecodeError::UnexpectedEndOfInput,
+            })
+        ));
+
+        let mut off_curve = proof.clone();
+        off_curve[..64].copy_from_slice(&g1_point_to_uncompressed_bytes(&off_curve_g1()));
+        assert!(matches!(
+            Groth16Verifier::verify(&off_curve, &vk, &public_inputs()),
+            Err(Error::MalformedInput {
+                offset: 0,
+                field: "ar",
+                kind: DecodeError::InvalidPoint,
+            })
+        ));
+
+        let mut long_prefix = proof.clone();
+        long_prefix[GROTH16_COMMITMENTS_OFFSET..GROTH16_COMMITMENTS_OFFSET + 4]
+            .copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            Groth16Verifier::verify(&long_prefix, &vk, &public_inputs()),
+            Err(Error::MalformedInput {
+                offset: GROTH16_COMMITMENTS_OFFSET,
+                field: "commitments",
+                kind: DecodeError::InvalidLengthPrefix,
+            })
+        ));
+
+        let mut trailing = proof.clone();
+        trailing.push(0);
+        assert!(Groth16Verifier::verify(&trailing, &vk, &public_inputs()).is_ok());
+        assert!(matches!(
+            Groth16Verifier::verify_strict(&trailing, &vk, &public_inputs()),
+            Err(Error::MalformedInput {
+                offset: GROTH16_PROOF_SIZE,
+                field: "end of input",
+                kind: DecodeError::TrailingBytes,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_groth16_malformed_verifying_key() {
+        let (proof, vk) = groth16_bytes();
+
+        // α is followed by β in G1
+        assert!(matches!(
+            Groth16Verifier::verify(&proof, &vk[..100], &public_inputs()),
+            Err(Error::MalformedInput {
+                offset: 64,
+                field: "beta_g1",
+                kind: DecodeError::UnexpectedEndOfInput,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_plonk_malformed_inputs() {
+        let vk = plonk_vk().to_gnark_bytes().unwrap();
+
+        // The size and its inverse are followed by the generator
+        assert!(matches!(
+            PlonkVerifier::prepare(&vk[..50]),
+            Err(Error::MalformedInput {
+                offset: 40,
+                field: "generator",
+                kind: DecodeError::UnexpectedEndOfInput,
+            })
+        ));
+
+        // The proof starts with the three wire commitments
+        let proof = g1_point_to_uncompressed_bytes(&g1(fr(1)));
+        assert!(matches!(
+            PlonkVerifier::verify(&proof, &vk, &public_inputs()),
+            Err(Error::MalformedInput {
+                offset: 64,
+                field: "lro",
+                kind: DecodeError::UnexpectedEndOfInput,
+            })
+        ));
+    }
 }
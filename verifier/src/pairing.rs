@@ -0,0 +1,595 @@
+//! Optimal ate pairing over BN254 with precomputed G2 lines, following gnark-crypto's
+//! `PrecomputeLines` and `MillerLoopFixedQ`.
+//!
+//! `bn` only exposes the pairing as a whole, so the Miller loop and the final exponentiation
+//! are carried out here over our own Fp6 = Fp2[v]/(v³ - ξ) and Fp12 = Fp6[w]/(w² - v) towers,
+//! with ξ = 9 + u, which are the towers used by gnark-crypto and `bn`.
+
+use alloc::vec::Vec;
+use bn::{AffineG1, AffineG2, Fq, Fq2};
+
+/// The affine line ℓ = R0 * x + R1 passing through the accumulated point in the Miller loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct LineEvaluationAff {
+    pub(crate) r0: Fq2,
+    pub(crate) r1: Fq2,
+}
+
+impl Default for LineEvaluationAff {
+    fn default() -> Self {
+        Self {
+            r0: Fq2::zero(),
+            r1: Fq2::zero(),
+        }
+    }
+}
+
+/// The number of iterations of the Miller loop, `len(LoopCounter)` in gnark-crypto.
+pub(crate) const LOOP_LENGTH: usize = 66;
+
+/// The lines of a G2 point: `[0][i]` is the doubling line of iteration `i` and `[1][i]` the
+/// addition line, when there is one. `[0][65]` and `[1][65]` are the lines of the final
+/// Frobenius additions.
+pub(crate) type PrecomputedLines = [[LineEvaluationAff; LOOP_LENGTH]; 2];
+
+// 2-NAF decomposition of 6x₀+2, little endian
+const LOOP_COUNTER: [i8; LOOP_LENGTH] = [
+    0, 0, 0, 1, 0, 1, 0, -1, 0, 0, -1, 0, 0, 0, 1, 0, 0, -1, 0, -1, 0, 0, 0, 1, 0, -1, 0, 0, 0, 0,
+    -1, 0, 0, 1, 0, -1, 0, 0, 1, 0, 0, 0, 0, 0, -1, 0, 0, -1, 0, 1, 0, -1, 0, 0, 0, -1, 0, -1, 0,
+    0, 0, 1, 0, -1, 0, 1,
+];
+
+// x₀, the seed of BN254
+const SEED: u64 = 4965661367192848881;
+
+// ξ^((p-1)/6 * k) for k = 1..5, the coefficients of the Frobenius map
+const FROBENIUS_COEFFS: [(&str, &str); 5] = [
+    (
+        "8376118865763821496583973867626364092589906065868298776909617916018768340080",
+        "16469823323077808223889137241176536799009286646108169935659301613961712198316",
+    ),
+    (
+        "21575463638280843010398324269430826099269044274347216827212613867836435027261",
+        "10307601595873709700152284273816112264069230130616436755625194854815875713954",
+    ),
+    (
+        "2821565182194536844548159561693502659359617185244120367078079554186484126554",
+        "3505843767911556378687030309984248845540243509899259641013678093033130930403",
+    ),
+    (
+        "2581911344467009335267311115468803099551665605076196740867805258568234346338",
+        "19937756971775647987995932169929341994314640652964949448313374472400716661030",
+    ),
+    (
+        "685108087231508774477564247770172212460312782337200605669322048753928464687",
+        "8447204650696766136447902020341177575205426561248465145919723016860428151883",
+    ),
+];
+
+fn frobenius_coeffs() -> [Fq2; 5] {
+    FROBENIUS_COEFFS.map(|(real, imaginary)| {
+        Fq2::new(
+            Fq::from_str(real).expect("valid constant"),
+            Fq::from_str(imaginary).expect("valid constant"),
+        )
+    })
+}
+
+fn fq2_conjugate(a: Fq2) -> Fq2 {
+    Fq2::new(a.real(), -a.imaginary())
+}
+
+fn fq2_mul_by_fq(a: Fq2, b: Fq) -> Fq2 {
+    Fq2::new(a.real() * b, a.imaginary() * b)
+}
+
+// (a0 + a1 u) * (9 + u)
+fn fq2_mul_by_nonresidue(a: Fq2) -> Fq2 {
+    let a0 = a.real();
+    let a1 = a.imaginary();
+    let a0_times_8 = double(double(double(a0)));
+    let a1_times_8 = double(double(double(a1)));
+
+    Fq2::new(a0_times_8 + a0 - a1, a1_times_8 + a1 + a0)
+}
+
+fn double(a: Fq) -> Fq {
+    a + a
+}
+
+fn fq2_inverse(a: Fq2) -> Option<Fq2> {
+    // u² = -1, so (a0 + a1 u)⁻¹ = (a0 - a1 u) / (a0² + a1²)
+    let norm = a.real() * a.real() + a.imaginary() * a.imaginary();
+    let norm_inv = norm.inverse()?;
+
+    Some(fq2_mul_by_fq(fq2_conjugate(a), norm_inv))
+}
+
+fn fq2_div(a: Fq2, b: Fq2) -> Option<Fq2> {
+    Some(a * fq2_inverse(b)?)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Fq6 {
+    c0: Fq2,
+    c1: Fq2,
+    c2: Fq2,
+}
+
+impl Fq6 {
+    fn zero() -> Self {
+        Self {
+            c0: Fq2::zero(),
+            c1: Fq2::zero(),
+            c2: Fq2::zero(),
+        }
+    }
+
+    fn one() -> Self {
+        Self {
+            c0: Fq2::one(),
+            ..Self::zero()
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self {
+            c0: self.c0 + other.c0,
+            c1: self.c1 + other.c1,
+            c2: self.c2 + other.c2,
+        }
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        Self {
+            c0: self.c0 - other.c0,
+            c1: self.c1 - other.c1,
+            c2: self.c2 - other.c2,
+        }
+    }
+
+    fn neg(&self) -> Self {
+        Self {
+            c0: -self.c0,
+            c1: -self.c1,
+            c2: -self.c2,
+        }
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let v0 = self.c0 * other.c0;
+        let v1 = self.c1 * other.c1;
+        let v2 = self.c2 * other.c2;
+
+        Self {
+            c0: v0 + fq2_mul_by_nonresidue((self.c1 + self.c2) * (other.c1 + other.c2) - v1 - v2),
+            c1: (self.c0 + self.c1) * (other.c0 + other.c1) - v0 - v1 + fq2_mul_by_nonresidue(v2),
+            c2: (self.c0 + self.c2) * (other.c0 + other.c2) - v0 - v2 + v1,
+        }
+    }
+
+    // self * (b0 + b1 v)
+    fn mul_by_01(&self, b0: Fq2, b1: Fq2) -> Self {
+        let v0 = self.c0 * b0;
+        let v1 = self.c1 * b1;
+
+        Self {
+            c0: v0 + fq2_mul_by_nonresidue((self.c1 + self.c2) * b1 - v1),
+            c1: (self.c0 + self.c1) * (b0 + b1) - v0 - v1,
+            c2: (self.c0 + self.c2) * b0 - v0 + v1,
+        }
+    }
+
+    // self * v
+    fn mul_by_nonresidue(&self) -> Self {
+        Self {
+            c0: fq2_mul_by_nonresidue(self.c2),
+            c1: self.c0,
+            c2: self.c1,
+        }
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        let t0 = self.c0 * self.c0 - fq2_mul_by_nonresidue(self.c1 * self.c2);
+        let t1 = fq2_mul_by_nonresidue(self.c2 * self.c2) - self.c0 * self.c1;
+        let t2 = self.c1 * self.c1 - self.c0 * self.c2;
+
+        let norm = self.c0 * t0
+            + fq2_mul_by_nonresidue(self.c2 * t1)
+            + fq2_mul_by_nonresidue(self.c1 * t2);
+        let norm_inv = fq2_inverse(norm)?;
+
+        Some(Self {
+            c0: t0 * norm_inv,
+            c1: t1 * norm_inv,
+            c2: t2 * norm_inv,
+        })
+    }
+}
+
+/// An element of Fp12, in which the pairing takes its values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Fq12 {
+    c0: Fq6,
+    c1: Fq6,
+}
+
+impl Fq12 {
+    pub(crate) fn one() -> Self {
+        Self {
+            c0: Fq6::one(),
+            c1: Fq6::zero(),
+        }
+    }
+
+    pub(crate) fn is_one(&self) -> bool {
+        *self == Self::one()
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let v0 = self.c0.mul(&other.c0);
+        let v1 = self.c1.mul(&other.c1);
+
+        Self {
+            c0: v0.add(&v1.mul_by_nonresidue()),
+            c1: self
+                .c0
+                .add(&self.c1)
+                .mul(&other.c0.add(&other.c1))
+                .sub(&v0)
+                .sub(&v1),
+        }
+    }
+
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    // The inverse of an element of the cyclotomic subgroup.
+    fn conjugate(&self) -> Self {
+        Self {
+            c0: self.c0,
+            c1: self.c1.neg(),
+        }
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        let norm = self
+            .c0
+            .mul(&self.c0)
+            .sub(&self.c1.mul(&self.c1).mul_by_nonresidue());
+        let norm_inv = norm.inverse()?;
+
+        Some(Self {
+            c0: self.c0.mul(&norm_inv),
+            c1: self.c1.mul(&norm_inv).neg(),
+        })
+    }
+
+    // self * ℓ, where ℓ = 1 + (l0 + l1 v) w is an evaluated line
+    fn mul_by_line(&self, l0: Fq2, l1: Fq2) -> Self {
+        Self {
+            c0: self.c0.add(&self.c1.mul_by_01(l0, l1).mul_by_nonresidue()),
+            c1: self.c1.add(&self.c0.mul_by_01(l0, l1)),
+        }
+    }
+
+    // self^p, the coefficient of wᵏ being conjugated and multiplied by ξ^((p-1)/6 * k)
+    fn frobenius(&self, coeffs: &[Fq2; 5]) -> Self {
+        Self {
+            c0: Fq6 {
+                c0: fq2_conjugate(self.c0.c0),
+                c1: fq2_conjugate(self.c0.c1) * coeffs[1],
+                c2: fq2_conjugate(self.c0.c2) * coeffs[3],
+            },
+            c1: Fq6 {
+                c0: fq2_conjugate(self.c1.c0) * coeffs[0],
+                c1: fq2_conjugate(self.c1.c1) * coeffs[2],
+                c2: fq2_conjugate(self.c1.c2) * coeffs[4],
+            },
+        }
+    }
+
+    fn frobenius_n(&self, n: usize, coeffs: &[Fq2; 5]) -> Self {
+        (0..n).fold(*self, |acc, _| acc.frobenius(coeffs))
+    }
+
+    // self^-x₀, for an element of the cyclotomic subgroup
+    fn exp_by_neg_seed(&self) -> Self {
+        let mut result = Self::one();
+        for i in (0..64 - SEED.leading_zeros()).rev() {
+            result = result.square();
+            if (SEED >> i) & 1 == 1 {
+                result = result.mul(self);
+            }
+        }
+
+        result.conjugate()
+    }
+}
+
+struct G2Accumulator {
+    x: Fq2,
+    y: Fq2,
+}
+
+impl G2Accumulator {
+    // Doubles the accumulator and returns the tangent line.
+    fn double_step(&mut self) -> Option<LineEvaluationAff> {
+        // λ = 3x²/2y
+        let x_square = self.x * self.x;
+        let lambda = fq2_div(x_square + x_square + x_square, self.y + self.y)?;
+
+        // xr = λ²-2x, yr = λ(x-xr)-y
+        let xr = lambda * lambda - self.x - self.x;
+        let yr = lambda * (self.x - xr) - self.y;
+
+        let line = LineEvaluationAff {
+            r0: lambda,
+            r1: lambda * self.x - self.y,
+        };
+        self.x = xr;
+        self.y = yr;
+
+        Some(line)
+    }
+
+    // Adds `(x, y)` to the accumulator and returns the line through both points.
+    fn add_step(&mut self, x: Fq2, y: Fq2) -> Option<LineEvaluationAff> {
+        let line = self.line(x, y)?;
+
+        // xr = λ²-x1-x2, yr = λ(x1-xr)-y1
+        let xr = line.r0 * line.r0 - self.x - x;
+        let yr = line.r0 * (self.x - xr) - self.y;
+        self.x = xr;
+        self.y = yr;
+
+        Some(line)
+    }
+
+    // Computes 2 * self + (x, y) as (self + (x, y)) + self, and returns both lines.
+    fn double_and_add_step(
+        &mut self,
+        x: Fq2,
+        y: Fq2,
+    ) -> Option<(LineEvaluationAff, LineEvaluationAff)> {
+        // λ1 = (y1-y2)/(x1-x2), x3 = λ1²-x1-x2
+        let l1 = fq2_div(self.y - y, self.x - x)?;
+        let x3 = l1 * l1 - self.x - x;
+
+        // λ2 = -λ1-2y1/(x3-x1)
+        let l2 = -(fq2_div(self.y + self.y, x3 - self.x)? + l1);
+
+        // x4 = λ2²-x1-x3, y4 = λ2(x1-x4)-y1
+        let x4 = l2 * l2 - self.x - x3;
+        let y4 = l2 * (self.x - x4) - self.y;
+
+        let line1 = LineEvaluationAff {
+            r0: l1,
+            r1: l1 * self.x - self.y,
+        };
+        let line2 = LineEvaluationAff {
+            r0: l2,
+            r1: l2 * self.x - self.y,
+        };
+        self.x = x4;
+        self.y = y4;
+
+        Some((line1, line2))
+    }
+
+    // The line through the accumulator and `(x, y)`.
+    fn line(&self, x: Fq2, y: Fq2) -> Option<LineEvaluationAff> {
+        let lambda = fq2_div(y - self.y, x - self.x)?;
+
+        Some(LineEvaluationAff {
+            r0: lambda,
+            r1: lambda * self.x - self.y,
+        })
+    }
+}
+
+/// Computes the lines of the Miller loop for a fixed G2 point, like gnark-crypto's
+/// `PrecomputeLines`.
+///
+/// Returns `None` for the point at infinity, which has no lines.
+pub(crate) fn precompute_lines(q: &AffineG2) -> Option<PrecomputedLines> {
+    let mut lines = [[LineEvaluationAff::default(); LOOP_LENGTH]; 2];
+    let mut acc = G2Accumulator { x: q.x(), y: q.y() };
+
+    for i in (0..LOOP_LENGTH - 1).rev() {
+        match LOOP_COUNTER[i] {
+            0 => lines[0][i] = acc.double_step()?,
+            1 => (lines[0][i], lines[1][i]) = acc.double_and_add_step(q.x(), q.y())?,
+            _ => (lines[0][i], lines[1][i]) = acc.double_and_add_step(q.x(), -q.y())?,
+        }
+    }
+
+    // π(Q) and -π²(Q)
+    let coeffs = frobenius_coeffs();
+    let phi_x = fq2_conjugate(q.x()) * coeffs[1];
+    let phi_y = fq2_conjugate(q.y()) * coeffs[2];
+    let psi_x = fq2_conjugate(phi_x) * coeffs[1];
+    let psi_y = -(fq2_conjugate(phi_y) * coeffs[2]);
+
+    lines[0][LOOP_LENGTH - 1] = acc.add_step(phi_x, phi_y)?;
+    lines[1][LOOP_LENGTH - 1] = acc.line(psi_x, psi_y)?;
+
+    Some(lines)
+}
+
+/// Computes the first line of the Miller loop for a fixed G2 point, the tangent at the point,
+/// which is enough to tell whether lines were precomputed for it.
+pub(crate) fn first_line(q: &AffineG2) -> Option<LineEvaluationAff> {
+    G2Accumulator { x: q.x(), y: q.y() }.double_step()
+}
+
+/// Computes the product of the Miller loops of the pairs, each G2 point being given by its
+/// precomputed lines, like gnark-crypto's `MillerLoopFixedQ`.
+///
+/// G1 points at infinity are skipped, since they pair to 1.
+pub(crate) fn miller_loop_fixed_q(pairs: &[(AffineG1, &PrecomputedLines)]) -> Fq12 {
+    // The lines are evaluated at P = (x, y) scaled by 1/y, which the final exponentiation
+    // cancels out: ℓ(P) = 1 + (R0 * (-x/y) + R1 * (1/y) v) w
+    let mut evaluations = Vec::with_capacity(pairs.len());
+    for (p, lines) in pairs {
+        if let Some(y_inv) = p.y().inverse() {
+            evaluations.push((-(p.x() * y_inv), y_inv, *lines));
+        }
+    }
+
+    let mul_by_line = |result: Fq12, line: &LineEvaluationAff, x_neg_over_y: Fq, y_inv: Fq| {
+        result.mul_by_line(
+            fq2_mul_by_fq(line.r0, x_neg_over_y),
+            fq2_mul_by_fq(line.r1, y_inv),
+        )
+    };
+
+    let mut result = Fq12::one();
+    for i in (0..LOOP_LENGTH - 1).rev() {
+        result = result.square();
+
+        for (x_neg_over_y, y_inv, lines) in evaluations.iter() {
+            result = mul_by_line(result, &lines[0][i], *x_neg_over_y, *y_inv);
+            if LOOP_COUNTER[i] != 0 {
+                result = mul_by_line(result, &lines[1][i], *x_neg_over_y, *y_inv);
+            }
+        }
+    }
+
+    for (x_neg_over_y, y_inv, lines) in evaluations.iter() {
+        result = mul_by_line(result, &lines[0][LOOP_LENGTH - 1], *x_neg_over_y, *y_inv);
+        result = mul_by_line(result, &lines[1][LOOP_LENGTH - 1], *x_neg_over_y, *y_inv);
+    }
+
+    result
+}
+
+/// Raises the output of the Miller loop to the power (p¹² - 1)/r.
+///
+/// The hard part follows Fuentes-Castañeda et al., "Faster hashing to G2", and computes a
+/// power of the pairing coprime to r, which is enough to compare it with 1. Returns `None` if
+/// `f` is zero.
+pub(crate) fn final_exponentiation(f: &Fq12) -> Option<Fq12> {
+    let coeffs = frobenius_coeffs();
+
+    // Easy part: f^((p⁶ - 1)(p² + 1))
+    let mut r = f.conjugate().mul(&f.inverse()?);
+    r = r.frobenius_n(2, &coeffs).mul(&r);
+
+    // Hard part: (p⁴ - p² + 1)/r
+    let y0 = r.exp_by_neg_seed();
+    let y1 = y0.square();
+    let y2 = y1.square();
+    let y3 = y2.mul(&y1);
+    let y4 = y3.exp_by_neg_seed();
+    let y5 = y4.square();
+    let y6 = y5.exp_by_neg_seed().conjugate();
+    let y3 = y3.conjugate();
+    let y7 = y6.mul(&y4);
+    let y8 = y7.mul(&y3);
+    let y9 = y8.mul(&y1);
+    let y10 = y8.mul(&y4);
+    let y11 = y10.mul(&r);
+    let y13 = y9.frobenius(&coeffs).mul(&y11);
+    let y14 = y8.frobenius_n(2, &coeffs).mul(&y13);
+    let y15 = r.conjugate().mul(&y9).frobenius_n(3, &coeffs);
+
+    Some(y15.mul(&y14))
+}
+
+/// Checks that ∏ e(Pᵢ, Qᵢ) == 1, each Qᵢ being given by its precomputed lines.
+pub(crate) fn pairing_check_fixed_q(pairs: &[(AffineG1, &PrecomputedLines)]) -> bool {
+    final_exponentiation(&miller_loop_fixed_q(pairs)).is_some_and(|result| result.is_one())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        converter::g2_identity,
+        test_utils::{g1, g2},
+    };
+    use bn::{pairing_batch, Fr, Gt, G1, G2};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Compares the fixed-Q pairing check with `bn`, which does not go through precomputed lines
+    fn check_against_bn(pairs: &[(AffineG1, AffineG2)]) -> bool {
+        let lines: Vec<_> = pairs
+            .iter()
+            .map(|(_, q)| precompute_lines(q).expect("lines of a non-identity point"))
+            .collect();
+        let fixed_q_pairs: Vec<_> = pairs
+            .iter()
+            .zip(lines.iter())
+            .map(|((p, _), l)| (*p, l))
+            .collect();
+        let bn_pairs: Vec<_> = pairs
+            .iter()
+            .map(|(p, q)| (G1::from(*p), G2::from(*q)))
+            .collect();
+
+        let expected = pairing_batch(&bn_pairs) == Gt::one();
+        assert_eq!(pairing_check_fixed_q(&fixed_q_pairs), expected);
+
+        expected
+    }
+
+    #[test]
+    fn test_pairing_check_fixed_q_matches_bn_on_random_inputs() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for nb_pairs in 1..4 {
+            let pairs: Vec<_> = (0..nb_pairs)
+                .map(|_| (g1(Fr::random(&mut rng)), g2(Fr::random(&mut rng))))
+                .collect();
+            assert!(!check_against_bn(&pairs));
+        }
+    }
+
+    #[test]
+    fn test_pairing_check_fixed_q_matches_bn_on_bilinear_inputs() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..3 {
+            // e(aP, bQ) * e(-abP, Q) == 1
+            let (a, b) = (Fr::random(&mut rng), Fr::random(&mut rng));
+            let pairs = [(g1(a), g2(b)), (g1(-(a * b)), g2(Fr::one()))];
+            assert!(check_against_bn(&pairs));
+
+            // e(aP, bQ) * e(-aP, Q) != 1
+            let pairs = [(g1(a), g2(b)), (g1(-a), g2(Fr::one()))];
+            assert!(!check_against_bn(&pairs));
+        }
+    }
+
+    #[test]
+    fn test_pairing_check_fixed_q_matches_bn_on_identity_inputs() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let q = g2(Fr::random(&mut rng));
+        let p = g1(Fr::random(&mut rng));
+
+        // G1 points at infinity pair to 1
+        assert!(check_against_bn(&[(AffineG1::default(), q)]));
+        assert!(check_against_bn(&[
+            (AffineG1::default(), q),
+            (AffineG1::default(), q)
+        ]));
+        assert!(!check_against_bn(&[(AffineG1::default(), q), (p, q)]));
+        assert!(pairing_check_fixed_q(&[]));
+    }
+
+    #[test]
+    fn test_precompute_lines_rejects_identity() {
+        assert!(precompute_lines(&g2_identity()).is_none());
+    }
+
+    #[test]
+    fn test_first_line_is_the_tangent_line() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let q = g2(Fr::random(&mut rng));
+        let lines = precompute_lines(&q).expect("lines of a non-identity point");
+
+        assert_eq!(first_line(&q), Some(lines[0][LOOP_LENGTH - 2]));
+    }
+}
@@ -1,109 +1,123 @@
 use crate::{
-    converter::{
-        unchecked_compressed_x_to_g1_point, unchecked_compressed_x_to_g2_point,
-        uncompressed_bytes_to_g1_point,
-    },
-    error::Error,
+    converter::{check_g1_point, check_g2_point, PointEncoding},
+    error::{ArgumentError, Error},
+    pairing::{first_line, LineEvaluationAff, LOOP_LENGTH},
+    reader::{ByteReader, ParseMode, VkValidation},
+    writer::ByteWriter,
 };
 use alloc::vec::Vec;
-use bn::{AffineG1, Fr, G2};
+use bn::AffineG2;
 
 use super::{
-    error::PlonkError,
-    kzg::{self, BatchOpeningProof, LineEvaluationAff, OpeningProof, E2},
+    kzg::{self, BatchOpeningProof, OpeningProof},
     verify::PlonkVerifyingKey,
-    PlonkProof,
+    GnarkVersion, PlonkProof,
 };
 
+// The offset of S[0], the first point of a verifying key, after the size, its inverse, the
+// generator, the number of public variables and the coset shift
+const VK_FIRST_POINT_OFFSET: usize = 8 + 32 + 32 + 8 + 32;
+
+// The size of a serialized pairing line: R0 and R1, each made of two Fq elements
+const LINE_SIZE: usize = 4 * 32;
+
+/// Parses a verifying key, detecting the gnark release it was serialized by.
 pub(crate) fn load_plonk_verifying_key_from_bytes(
     buffer: &[u8],
-) -> Result<PlonkVerifyingKey, PlonkError> {
-    let size = u64::from_be_bytes([
-        buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6], buffer[7],
-    ]) as usize;
-    let size_inv =
-        Fr::from_slice(&buffer[8..40]).map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
-    let generator =
-        Fr::from_slice(&buffer[40..72]).map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
-
-    let nb_public_variables = u64::from_be_bytes([
-        buffer[72], buffer[73], buffer[74], buffer[75], buffer[76], buffer[77], buffer[78],
-        buffer[79],
-    ]) as usize;
-
-    let coset_shift =
-        Fr::from_slice(&buffer[80..112]).map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
-    let s0 = unchecked_compressed_x_to_g1_point(&buffer[112..144])?;
-    let s1 = unchecked_compressed_x_to_g1_point(&buffer[144..176])?;
-    let s2 = unchecked_compressed_x_to_g1_point(&buffer[176..208])?;
-    let ql = unchecked_compressed_x_to_g1_point(&buffer[208..240])?;
-    let qr = unchecked_compressed_x_to_g1_point(&buffer[240..272])?;
-    let qm = unchecked_compressed_x_to_g1_point(&buffer[272..304])?;
-    let qo = unchecked_compressed_x_to_g1_point(&buffer[304..336])?;
-    let qk = unchecked_compressed_x_to_g1_point(&buffer[336..368])?;
-    let num_qcp = u32::from_be_bytes([buffer[368], buffer[369], buffer[370], buffer[371]]);
-    let mut qcp = Vec::new();
-    let mut offset = 372;
+    mode: ParseMode,
+    validation: VkValidation,
+) -> Result<PlonkVerifyingKey, Error> {
+    load_plonk_verifying_key(buffer, mode, validation, None)
+}
+
+/// Parses a verifying key serialized by the given gnark release.
+pub(crate) fn load_plonk_verifying_key_with_version(
+    buffer: &[u8],
+    mode: ParseMode,
+    validation: VkValidation,
+    version: GnarkVersion,
+) -> Result<PlonkVerifyingKey, Error> {
+    load_plonk_verifying_key(buffer, mode, validation, Some(version))
+}
+
+fn load_plonk_verifying_key(
+    buffer: &[u8],
+    mode: ParseMode,
+    validation: VkValidation,
+    version: Option<GnarkVersion>,
+) -> Result<PlonkVerifyingKey, Error> {
+    // Keys written by gnark's `WriteTo` are compressed, those written by `WriteRawTo` are not
+    let encoding = PointEncoding::detect(buffer.get(VK_FIRST_POINT_OFFSET..).unwrap_or_default());
+    let mut reader = ByteReader::new(buffer);
 
+    let size = reader.read_u64_as("size")?;
+    let size_inv = reader.read_fr("size_inv")?;
+    let generator = reader.read_fr("generator")?;
+
+    let nb_public_variables = reader.read_u64_as("nb_public_variables")?;
+
+    let coset_shift = reader.read_fr("coset_shift")?;
+    let s0 = reader.read_unchecked_g1(encoding, "s")?;
+    let s1 = reader.read_unchecked_g1(encoding, "s")?;
+    let s2 = reader.read_unchecked_g1(encoding, "s")?;
+    let ql = reader.read_unchecked_g1(encoding, "ql")?;
+    let qr = reader.read_unchecked_g1(encoding, "qr")?;
+    let qm = reader.read_unchecked_g1(encoding, "qm")?;
+    let qo = reader.read_unchecked_g1(encoding, "qo")?;
+    let qk = reader.read_unchecked_g1(encoding, "qk")?;
+
+    let num_qcp = reader.read_len_u32(encoding.g1_size(), "qcp")?;
+    let mut qcp = Vec::with_capacity(num_qcp);
     for _ in 0..num_qcp {
-        let point = unchecked_compressed_x_to_g1_point(&buffer[offset..offset + 32])?;
-        qcp.push(point);
-        offset += 32;
-    }
-
-    let g1 = unchecked_compressed_x_to_g1_point(&buffer[offset..offset + 32])?;
-    let g2_0 = unchecked_compressed_x_to_g2_point(&buffer[offset + 32..offset + 96])?;
-    let g2_1 = unchecked_compressed_x_to_g2_point(&buffer[offset + 96..offset + 160])?;
-
-    offset += 160 + 33788;
-
-    let num_commitment_constraint_indexes = u64::from_be_bytes([
-        buffer[offset],
-        buffer[offset + 1],
-        buffer[offset + 2],
-        buffer[offset + 3],
-        buffer[offset + 4],
-        buffer[offset + 5],
-        buffer[offset + 6],
-        buffer[offset + 7],
-    ]) as usize;
-
-    let mut commitment_constraint_indexes = Vec::new();
-    offset += 8;
+        qcp.push(reader.read_unchecked_g1(encoding, "qcp")?);
+    }
+
+    let g1 = reader.read_unchecked_g1(encoding, "kzg_g1")?;
+    let g2_0 = reader.read_unchecked_g2(encoding, "kzg_g2")?;
+    let g2_1 = reader.read_unchecked_g2(encoding, "kzg_g2")?;
+
+    // The pairing lines are checked against the G2 points, so these must be valid first
+    if validation == VkValidation::Full {
+        check_g1_point(&g1, "kzg_g1")?;
+        check_g2_point(&g2_0, "kzg_g2")?;
+        check_g2_point(&g2_1, "kzg_g2")?;
+    }
+
+    let version = match version {
+        Some(version) => version,
+        None => detect_version(&reader, &g2_0, mode)?,
+    };
+    let kzg = if version.has_kzg_lines() {
+        let mut lines = [[[LineEvaluationAff::default(); LOOP_LENGTH]; 2]; 2];
+        for line in lines.iter_mut().flatten().flatten() {
+            *line = reader.read_line("kzg_lines")?;
+        }
+
+        // Precomputing the lines again is only worth it for untrusted keys
+        let kzg = kzg::KZGVerifyingKey::with_lines(g1, [g2_0, g2_1], lines)?;
+        if validation == VkValidation::Full {
+            kzg.check_lines()?;
+        }
+        kzg
+    } else {
+        kzg::KZGVerifyingKey::new(g1, [g2_0, g2_1])?
+    };
+
+    let num_commitment_constraint_indexes =
+        reader.read_len_u32(8, "commitment_constraint_indexes")?;
+    let mut commitment_constraint_indexes = Vec::with_capacity(num_commitment_constraint_indexes);
     for _ in 0..num_commitment_constraint_indexes {
-        let index = u64::from_be_bytes([
-            buffer[offset],
-            buffer[offset + 1],
-            buffer[offset + 2],
-            buffer[offset + 3],
-            buffer[offset + 4],
-            buffer[offset + 5],
-            buffer[offset + 6],
-            buffer[offset + 7],
-        ]) as usize;
-        commitment_constraint_indexes.push(index);
-        offset += 8;
+        commitment_constraint_indexes.push(reader.read_u64_as("commitment_constraint_indexes")?);
     }
 
+    reader.finish(mode)?;
+
     let result = PlonkVerifyingKey {
         size,
         size_inv,
         generator,
         nb_public_variables,
-        kzg: kzg::KZGVerifyingKey {
-            g2: [G2::from(g2_0), G2::from(g2_1)],
-            g1: g1.into(),
-            lines: [[[LineEvaluationAff {
-                r0: E2 {
-                    a0: Fr::zero(),
-                    a1: Fr::zero(),
-                },
-                r1: E2 {
-                    a0: Fr::zero(),
-                    a1: Fr::zero(),
-                },
-            }; 66]; 2]; 2],
-        },
+        kzg,
         coset_shift,
         s: [s0, s1, s2],
         ql,
@@ -113,52 +127,99 @@ pub(crate) fn load_plonk_verifying_key_from_bytes(
         qk,
         qcp,
         commitment_constraint_indexes,
+        version,
+        encoding,
     };
 
+    if validation == VkValidation::Full {
+        check_verifying_key_points(&result)?;
+        result.validate()?;
+    }
+
     Ok(result)
 }
 
-pub(crate) fn load_plonk_proof_from_bytes(buffer: &[u8]) -> Result<PlonkProof, PlonkError> {
-    let lro0 = uncompressed_bytes_to_g1_point(&buffer[..64])?;
-    let lro1 = uncompressed_bytes_to_g1_point(&buffer[64..128])?;
-    let lro2 = uncompressed_bytes_to_g1_point(&buffer[128..192])?;
-    let z = uncompressed_bytes_to_g1_point(&buffer[192..256])?;
-    let h0 = uncompressed_bytes_to_g1_point(&buffer[256..320])?;
-    let h1 = uncompressed_bytes_to_g1_point(&buffer[320..384])?;
-    let h2 = uncompressed_bytes_to_g1_point(&buffer[384..448])?;
-    let batched_proof_h = uncompressed_bytes_to_g1_point(&buffer[448..512])?;
-
-    let num_claimed_values =
-        u32::from_be_bytes([buffer[512], buffer[513], buffer[514], buffer[515]]) as usize;
-
-    let mut claimed_values = Vec::new();
-    let mut offset = 516;
-    for _ in 0..num_claimed_values {
-        let value = Fr::from_slice(&buffer[offset..offset + 32])
-            .map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
-        claimed_values.push(value);
-        offset += 32;
+fn check_verifying_key_points(vk: &PlonkVerifyingKey) -> Result<(), Error> {
+    for s in &vk.s {
+        check_g1_point(s, "s")?;
+    }
+    check_g1_point(&vk.ql, "ql")?;
+    check_g1_point(&vk.qr, "qr")?;
+    check_g1_point(&vk.qm, "qm")?;
+    check_g1_point(&vk.qo, "qo")?;
+    check_g1_point(&vk.qk, "qk")?;
+    for qcp in &vk.qcp {
+        check_g1_point(qcp, "qcp")?;
     }
 
-    let z_shifted_opening_h = uncompressed_bytes_to_g1_point(&buffer[offset..offset + 64])?;
-    let z_shifted_opening_value = Fr::from_slice(&buffer[offset + 64..offset + 96])
-        .map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
+    Ok(())
+}
+
+// From v0.10 on, the pairing lines of G₂ and [α]G₂ follow [α]G₂. Their block starts with the
+// lines of G₂, whose first line in the Miller loop, the tangent at G₂, is the 65th one. Both
+// layouts then end with the commitment constraint indexes, so the rest of the key must fit
+// exactly one of them. v0.10 and v0.11 write the same bytes, such keys are reported as v0.11.
+fn detect_version(
+    reader: &ByteReader,
+    g2: &AffineG2,
+    mode: ParseMode,
+) -> Result<GnarkVersion, Error> {
+    let fits = |has_kzg_lines: bool| -> Result<(), Error> {
+        let mut lookahead = reader.clone();
+        if has_kzg_lines {
+            lookahead.read_bytes((LOOP_LENGTH - 2) * LINE_SIZE, "kzg_lines")?;
+            if first_line(g2) != Some(lookahead.read_line("kzg_lines")?) {
+                return Err(ArgumentError::UnsupportedFormatVersion.into());
+            }
+            lookahead.read_bytes((3 * LOOP_LENGTH + 1) * LINE_SIZE, "kzg_lines")?;
+        }
 
-    let num_bsb22_commitments = u32::from_be_bytes([
-        buffer[offset + 96],
-        buffer[offset + 97],
-        buffer[offset + 98],
-        buffer[offset + 99],
-    ]) as usize;
+        let nb_indexes = lookahead.read_len_u32(8, "commitment_constraint_indexes")?;
+        lookahead.read_bytes(8 * nb_indexes, "commitment_constraint_indexes")?;
+        lookahead.finish(mode)
+    };
+
+    match (fits(true).is_ok(), fits(false).is_ok()) {
+        (true, false) => Ok(GnarkVersion::V0_11),
+        (false, true) => Ok(GnarkVersion::V0_9),
+        _ => Err(ArgumentError::UnsupportedFormatVersion.into()),
+    }
+}
 
-    let mut bsb22_commitments = Vec::new();
-    offset += 100;
+pub(crate) fn load_plonk_proof_from_bytes(
+    buffer: &[u8],
+    mode: ParseMode,
+) -> Result<PlonkProof, Error> {
+    // Proofs written by gnark's `WriteTo` are compressed, those written by `WriteRawTo` are not
+    let encoding = PointEncoding::detect(buffer);
+    let mut reader = ByteReader::new(buffer);
+
+    let lro0 = reader.read_g1(encoding, "lro")?;
+    let lro1 = reader.read_g1(encoding, "lro")?;
+    let lro2 = reader.read_g1(encoding, "lro")?;
+    let z = reader.read_g1(encoding, "z")?;
+    let h0 = reader.read_g1(encoding, "h")?;
+    let h1 = reader.read_g1(encoding, "h")?;
+    let h2 = reader.read_g1(encoding, "h")?;
+    let batched_proof_h = reader.read_g1(encoding, "batched_proof_h")?;
+
+    let num_claimed_values = reader.read_len_u32(32, "claimed_values")?;
+    let mut claimed_values = Vec::with_capacity(num_claimed_values);
+    for _ in 0..num_claimed_values {
+        claimed_values.push(reader.read_fr("claimed_values")?);
+    }
+
+    let z_shifted_opening_h = reader.read_g1(encoding, "z_shifted_opening_h")?;
+    let z_shifted_opening_value = reader.read_fr("z_shifted_opening_value")?;
+
+    let num_bsb22_commitments = reader.read_len_u32(encoding.g1_size(), "bsb22_commitments")?;
+    let mut bsb22_commitments = Vec::with_capacity(num_bsb22_commitments);
     for _ in 0..num_bsb22_commitments {
-        let commitment = uncompressed_bytes_to_g1_point(&buffer[offset..offset + 64])?;
-        bsb22_commitments.push(commitment);
-        offset += 64;
+        bsb22_commitments.push(reader.read_g1(encoding, "bsb22_commitments")?);
     }
 
+    reader.finish(mode)?;
+
     let result = PlonkProof {
         lro: [lro0, lro1, lro2],
         z,
@@ -172,14 +233,380 @@ pub(crate) fn load_plonk_proof_from_bytes(buffer: &[u8]) -> Result<PlonkProof, P
             h: z_shifted_opening_h,
             claimed_value: z_shifted_opening_value,
         },
+        encoding,
     };
 
     Ok(result)
 }
 
-pub(crate) fn g1_to_bytes(g1: &AffineG1) -> Result<Vec<u8>, PlonkError> {
-    let mut bytes: [u8; 64] = unsafe { core::mem::transmute(*g1) };
-    bytes[..32].reverse();
-    bytes[32..].reverse();
-    Ok(bytes.to_vec())
+pub(crate) fn plonk_verifying_key_to_bytes(vk: &PlonkVerifyingKey) -> Result<Vec<u8>, Error> {
+    let encoding = vk.encoding;
+    let mut writer = ByteWriter::new();
+
+    writer.write_u64(vk.size as u64);
+    writer.write_fr(&vk.size_inv);
+    writer.write_fr(&vk.generator);
+    writer.write_u64(vk.nb_public_variables as u64);
+    writer.write_fr(&vk.coset_shift);
+
+    for s in &vk.s {
+        writer.write_g1(s, encoding);
+    }
+    for q in [&vk.ql, &vk.qr, &vk.qm, &vk.qo, &vk.qk] {
+        writer.write_g1(q, encoding);
+    }
+
+    writer.write_len_u32(vk.qcp.len(), "qcp")?;
+    for qcp in &vk.qcp {
+        writer.write_g1(qcp, encoding);
+    }
+
+    writer.write_g1(&vk.kzg.g1, encoding);
+    writer.write_g2(&vk.kzg.g2[0], encoding);
+    writer.write_g2(&vk.kzg.g2[1], encoding);
+    if vk.version.has_kzg_lines() {
+        for line in vk.kzg.lines.iter().flatten().flatten() {
+            writer.write_line(line);
+        }
+    }
+
+    writer.write_len_u32(
+        vk.commitment_constraint_indexes.len(),
+        "commitment_constraint_indexes",
+    )?;
+    for index in &vk.commitment_constraint_indexes {
+        writer.write_u64(*index as u64);
+    }
+
+    Ok(writer.into_bytes())
+}
+
+pub(crate) fn plonk_proof_to_bytes(proof: &PlonkProof) -> Result<Vec<u8>, Error> {
+    let encoding = proof.encoding;
+    let mut writer = ByteWriter::new();
+
+    for lro in &proof.lro {
+        writer.write_g1(lro, encoding);
+    }
+    writer.write_g1(&proof.z, encoding);
+    for h in &proof.h {
+        writer.write_g1(h, encoding);
+    }
+    writer.write_g1(&proof.batched_proof.h, encoding);
+
+    writer.write_len_u32(proof.batched_proof.claimed_values.len(), "claimed_values")?;
+    for claimed_value in &proof.batched_proof.claimed_values {
+        writer.write_fr(claimed_value);
+    }
+
+    writer.write_g1(&proof.z_shifted_opening.h, encoding);
+    writer.write_fr(&proof.z_shifted_opening.claimed_value);
+
+    writer.write_len_u32(proof.bsb22_commitments.len(), "bsb22_commitments")?;
+    for commitment in &proof.bsb22_commitments {
+        writer.write_g1(commitment, encoding);
+    }
+
+    Ok(writer.into_bytes())
+}
+
+impl PlonkProof {
+    /// Parses a proof serialized by gnark, with compressed or uncompressed points.
+    ///
+    /// Unlike [`PlonkVerifier::verify`](crate::PlonkVerifier::verify), bytes left after the
+    /// proof are rejected, so that [`Self::to_gnark_bytes`] gives back the same bytes.
+    pub fn from_gnark_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        load_plonk_proof_from_bytes(bytes, ParseMode::Strict)
+    }
+
+    /// Serializes the proof in gnark's format, with its point encoding.
+    pub fn to_gnark_bytes(&self) -> Result<Vec<u8>, Error> {
+        plonk_proof_to_bytes(self)
+    }
+}
+
+impl PlonkVerifyingKey {
+    /// Parses a verifying key serialized by gnark, with compressed or uncompressed points,
+    /// detecting the gnark release from whether the pairing lines of the KZG verifying key are
+    /// present. Keys written by v0.10 are reported as v0.11, which serializes them the same way.
+    ///
+    /// Bytes that fit no supported layout, or more than one, are reported as
+    /// [`ArgumentError::UnsupportedFormatVersion`](crate::ArgumentError::UnsupportedFormatVersion).
+    ///
+    /// Bytes left after the key are rejected, so that [`Self::to_gnark_bytes`] gives back the
+    /// same bytes.
+    pub fn from_gnark_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        load_plonk_verifying_key_from_bytes(bytes, ParseMode::Strict, VkValidation::Trusted)
+    }
+
+    /// Parses a verifying key like [`Self::from_gnark_bytes`], checking its points as set by
+    /// `validation`.
+    pub fn from_gnark_bytes_with_validation(
+        bytes: &[u8],
+        validation: VkValidation,
+    ) -> Result<Self, Error> {
+        load_plonk_verifying_key_from_bytes(bytes, ParseMode::Strict, validation)
+    }
+
+    /// Parses a verifying key serialized by the given gnark release.
+    ///
+    /// See [`Self::from_gnark_bytes`].
+    pub fn from_gnark_bytes_with_version(
+        bytes: &[u8],
+        version: GnarkVersion,
+    ) -> Result<Self, Error> {
+        load_plonk_verifying_key_with_version(
+            bytes,
+            ParseMode::Strict,
+            VkValidation::Trusted,
+            version,
+        )
+    }
+
+    /// Serializes the verifying key in gnark's format, with its point encoding and in the
+    /// layout of [`Self::version`].
+    pub fn to_gnark_bytes(&self) -> Result<Vec<u8>, Error> {
+        plonk_verifying_key_to_bytes(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        converter::{g1_point_to_uncompressed_bytes, g2_point_to_uncompressed_bytes},
+        error::{DecodeError, VkError},
+        test_utils::{non_subgroup_g2, off_curve_g1, off_curve_g2, plonk_vk},
+    };
+
+    // The raw gnark proof of the SP1 fibonacci example, written with `WriteRawTo`
+    const FIBONACCI_PROOF: &[u8] = include_bytes!("../../fixtures/fibonacci_plonk_proof.raw");
+
+    // The offset of the first byte of the pairing lines of a compressed verifying key with one
+    // commitment selector
+    const KZG_LINES_OFFSET: usize = VK_FIRST_POINT_OFFSET + 9 * 32 + 4 + 32 + 2 * 64;
+
+    fn load(bytes: &[u8], validation: VkValidation) -> Result<PlonkVerifyingKey, Error> {
+        load_plonk_verifying_key_from_bytes(bytes, ParseMode::Strict, validation)
+    }
+
+    #[test]
+    fn test_verifying_key_round_trips() {
+        let versions = [GnarkVersion::V0_9, GnarkVersion::V0_11];
+        let encodings = [PointEncoding::Compressed, PointEncoding::Uncompressed];
+
+        for version in versions {
+            for encoding in encodings {
+                let vk = plonk_vk().with_version(version).with_encoding(encoding);
+                let bytes = vk.to_gnark_bytes().unwrap();
+                let parsed = PlonkVerifyingKey::from_gnark_bytes(&bytes).unwrap();
+
+                assert_eq!(parsed.version, version);
+                assert_eq!(parsed.encoding, encoding);
+                assert_eq!(parsed.to_gnark_bytes().unwrap(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_version() {
+        let encodings = [PointEncoding::Compressed, PointEncoding::Uncompressed];
+        let versions = [
+            (GnarkVersion::V0_9, GnarkVersion::V0_9),
+            (GnarkVersion::V0_10, GnarkVersion::V0_11),
+            (GnarkVersion::V0_11, GnarkVersion::V0_11),
+        ];
+
+        for (version, detected) in versions {
+            for encoding in encodings {
+                let bytes = plonk_vk()
+                    .with_version(version)
+                    .with_encoding(encoding)
+                    .to_gnark_bytes()
+                    .unwrap();
+
+                let parsed = PlonkVerifyingKey::from_gnark_bytes(&bytes).unwrap();
+                assert_eq!(parsed.version, detected);
+
+                let forced = PlonkVerifyingKey::from_gnark_bytes_with_version(&bytes, version);
+                assert_eq!(forced.unwrap().to_gnark_bytes().unwrap(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_version_rejects_unknown_layouts() {
+        let is_unsupported = |bytes: &[u8]| {
+            matches!(
+                PlonkVerifyingKey::from_gnark_bytes(bytes),
+                Err(Error::InvalidArgument(
+                    ArgumentError::UnsupportedFormatVersion
+                ))
+            )
+        };
+
+        for version in [GnarkVersion::V0_9, GnarkVersion::V0_11] {
+            let bytes = plonk_vk().with_version(version).to_gnark_bytes().unwrap();
+
+            // A missing or an extra byte fits neither layout
+            assert!(is_unsupported(&bytes[..bytes.len() - 1]));
+            let mut extended = bytes.clone();
+            extended.push(0);
+            assert!(is_unsupported(&extended));
+        }
+
+        // Lines whose tangent is not the one at G₂
+        let mut bytes = plonk_vk().to_gnark_bytes().unwrap();
+        bytes[KZG_LINES_OFFSET + (LOOP_LENGTH - 2) * LINE_SIZE + LINE_SIZE - 1] ^= 1;
+        assert!(is_unsupported(&bytes));
+
+        // Without the strict mode, bytes after the key are not part of its layout
+        let mut bytes = plonk_vk().to_gnark_bytes().unwrap();
+        bytes.extend_from_slice(&[0; 8]);
+        let vk =
+            load_plonk_verifying_key_from_bytes(&bytes, ParseMode::Lenient, VkValidation::Trusted);
+        assert_eq!(vk.unwrap().version, GnarkVersion::V0_11);
+    }
+
+    #[test]
+    fn test_forced_version_must_match_layout() {
+        let v0_9 = plonk_vk().with_version(GnarkVersion::V0_9);
+        let v0_10 = plonk_vk().with_version(GnarkVersion::V0_10);
+
+        // The lines are read as the commitment constraint indexes, leaving bytes behind
+        let bytes = v0_10.to_gnark_bytes().unwrap();
+        assert!(
+            PlonkVerifyingKey::from_gnark_bytes_with_version(&bytes, GnarkVersion::V0_9).is_err()
+        );
+
+        // The commitment constraint indexes are read as lines, and run out of bytes
+        let bytes = v0_9.to_gnark_bytes().unwrap();
+        assert!(matches!(
+            PlonkVerifyingKey::from_gnark_bytes_with_version(&bytes, GnarkVersion::V0_10),
+            Err(Error::MalformedInput {
+                field: "kzg_lines",
+                kind: DecodeError::UnexpectedEndOfInput,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_load_checks_every_kzg_line_under_full_validation() {
+        let bytes = plonk_vk().to_gnark_bytes().unwrap();
+        assert!(load(&bytes, VkValidation::Full).is_ok());
+
+        // The tangent at G₂, and lines of G₂ and [α]G₂ the Miller loop reaches later
+        let tangent = (LOOP_LENGTH - 2) * LINE_SIZE;
+        for line in [tangent, 0, 2 * LOOP_LENGTH * LINE_SIZE + 5 * LINE_SIZE] {
+            let mut tampered = bytes.clone();
+            tampered[KZG_LINES_OFFSET + line + LINE_SIZE - 1] ^= 1;
+            let load = |validation| {
+                load_plonk_verifying_key_with_version(
+                    &tampered,
+                    ParseMode::Strict,
+                    validation,
+                    GnarkVersion::V0_10,
+                )
+            };
+
+            // Trusted keys keep the serialized lines as they are
+            let trusted = load(VkValidation::Trusted).unwrap();
+            assert_eq!(trusted.to_gnark_bytes().unwrap(), tampered);
+            assert!(matches!(
+                load(VkValidation::Full),
+                Err(Error::InvalidVerifyingKey(VkError::InvalidPairingLines))
+            ));
+        }
+    }
+
+    // The offsets of [Ql]₁ and of the first KZG G2 point of an uncompressed verifying key with
+    // one commitment selector
+    const UNCOMPRESSED_QL_OFFSET: usize = VK_FIRST_POINT_OFFSET + 3 * 64;
+    const UNCOMPRESSED_KZG_G2_OFFSET: usize = VK_FIRST_POINT_OFFSET + 8 * 64 + 4 + 2 * 64;
+
+    fn uncompressed_v0_9_bytes() -> Vec<u8> {
+        plonk_vk()
+            .with_version(GnarkVersion::V0_9)
+            .with_encoding(PointEncoding::Uncompressed)
+            .to_gnark_bytes()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_full_validation_rejects_off_curve_g1_point() {
+        let mut bytes = uncompressed_v0_9_bytes();
+        bytes[UNCOMPRESSED_QL_OFFSET..UNCOMPRESSED_QL_OFFSET + 64]
+            .copy_from_slice(&g1_point_to_uncompressed_bytes(&off_curve_g1()));
+
+        assert!(load(&bytes, VkValidation::Trusted).is_ok());
+        assert!(matches!(
+            load(&bytes, VkValidation::Full),
+            Err(Error::InvalidVerifyingKey(VkError::InvalidPoint("ql")))
+        ));
+    }
+
+    #[test]
+    fn test_full_validation_rejects_invalid_g2_points() {
+        for point in [off_curve_g2(), non_subgroup_g2()] {
+            let mut bytes = uncompressed_v0_9_bytes();
+            bytes[UNCOMPRESSED_KZG_G2_OFFSET..UNCOMPRESSED_KZG_G2_OFFSET + 128]
+                .copy_from_slice(&g2_point_to_uncompressed_bytes(&point));
+
+            assert!(matches!(
+                load(&bytes, VkValidation::Full),
+                Err(Error::InvalidVerifyingKey(VkError::InvalidPoint("kzg_g2")))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_full_validation_validates_the_key() {
+        let mut vk = plonk_vk();
+        vk.size = 6;
+        let bytes = vk.to_gnark_bytes().unwrap();
+
+        assert!(load(&bytes, VkValidation::Trusted).is_ok());
+        assert!(matches!(
+            load(&bytes, VkValidation::Full),
+            Err(Error::InvalidVerifyingKey(VkError::InvalidDomainSize(6)))
+        ));
+    }
+
+    #[test]
+    fn test_gnark_proof_round_trips() {
+        let proof = PlonkProof::from_gnark_bytes(FIBONACCI_PROOF).unwrap();
+        assert_eq!(proof.encoding(), PointEncoding::Uncompressed);
+        assert_eq!(proof.to_gnark_bytes().unwrap(), FIBONACCI_PROOF);
+
+        let compressed = proof.clone().with_encoding(PointEncoding::Compressed);
+        let bytes = compressed.to_gnark_bytes().unwrap();
+        let parsed = PlonkProof::from_gnark_bytes(&bytes).unwrap();
+
+        assert_eq!(parsed.encoding(), PointEncoding::Compressed);
+        assert_eq!(parsed.lro(), proof.lro());
+        assert_eq!(parsed.claimed_values(), proof.claimed_values());
+        assert_eq!(parsed.bsb22_commitments(), proof.bsb22_commitments());
+        assert_eq!(parsed.to_gnark_bytes().unwrap(), bytes);
+        assert_eq!(
+            parsed
+                .with_encoding(PointEncoding::Uncompressed)
+                .to_gnark_bytes()
+                .unwrap(),
+            FIBONACCI_PROOF
+        );
+    }
+
+    #[test]
+    fn test_proof_rejects_trailing_bytes() {
+        let bytes = [FIBONACCI_PROOF, &[0]].concat();
+
+        assert!(matches!(
+            PlonkProof::from_gnark_bytes(&bytes),
+            Err(Error::MalformedInput {
+                kind: DecodeError::TrailingBytes,
+                ..
+            })
+        ));
+    }
 }
@@ -1,55 +1,159 @@
-use alloc::{string::ToString, vec, vec::Vec};
-use bn::{pairing_batch, AffineG1, Fr, G1, G2};
-use rand::rngs::OsRng;
+//! Verification of KZG polynomial commitment openings over BN254, compatible with gnark-crypto.
+
+use alloc::{vec, vec::Vec};
+use bn::{AffineG1, AffineG2, Fr, G2};
+use core::fmt;
+use sha2::Sha256;
+
+use crate::{
+    constants::{GAMMA, KZG_BATCH_CHALLENGE},
+    converter::{g1_to_bytes, is_g2_identity},
+    error::{Error, InternalError, ProofCheck, VkError},
+    hash_to_field::FiatShamirHash,
+    pairing::{
+        pairing_check_fixed_q, precompute_lines, LineEvaluationAff, PrecomputedLines, LOOP_LENGTH,
+    },
+    transcript::Transcript,
+};
+
+/// A KZG commitment to a polynomial.
+pub type Digest = AffineG1;
+
+/// The verifying key of a KZG commitment scheme over BN254, as in gnark-crypto.
+#[derive(Clone, Copy)]
+pub struct KZGVerifyingKey {
+    pub(crate) g2: [AffineG2; 2], // [G₂, [α]G₂]
+    pub(crate) g1: AffineG1,
+    // Precomputed pairing lines corresponding to G₂, [α]G₂
+    pub(crate) lines: [PrecomputedLines; 2],
+}
 
-use crate::{constants::GAMMA, error::Error, transcript::Transcript};
+impl KZGVerifyingKey {
+    /// Creates a verifying key from the G1 generator and [G₂, [α]G₂] of the SRS, precomputing
+    /// the pairing lines of the G2 points.
+    ///
+    /// # Returns
+    ///
+    /// The verifying key, or an [`Error::InvalidVerifyingKey`] if a G2 point is the identity,
+    /// which has no pairing lines.
+    pub fn new(g1: AffineG1, g2: [AffineG2; 2]) -> Result<Self, Error> {
+        let mut lines = [[[LineEvaluationAff::default(); LOOP_LENGTH]; 2]; 2];
+        for (q, lines) in g2.iter().zip(lines.iter_mut()) {
+            if is_g2_identity(q) {
+                return Err(VkError::IdentityPoint("kzg_g2").into());
+            }
+            *lines = precompute_lines(q).ok_or(VkError::InvalidPoint("kzg_g2"))?;
+        }
+
+        Ok(Self { g2, g1, lines })
+    }
 
-use super::{converter::g1_to_bytes, error::PlonkError};
+    /// Creates a verifying key with the pairing lines precomputed by gnark-crypto.
+    ///
+    /// The lines are trusted as they are, since precomputing them again costs as much as the
+    /// pairing checks that use them. Keys from untrusted sources must go through
+    /// [`Self::check_lines`].
+    pub(crate) fn with_lines(
+        g1: AffineG1,
+        g2: [AffineG2; 2],
+        lines: [PrecomputedLines; 2],
+    ) -> Result<Self, Error> {
+        if g2.iter().any(is_g2_identity) {
+            return Err(VkError::IdentityPoint("kzg_g2").into());
+        }
+
+        Ok(Self { g2, g1, lines })
+    }
 
-pub(crate) type Digest = AffineG1;
+    /// Checks that the pairing lines are those of the G2 points, by precomputing them again.
+    ///
+    /// The pairing checks trust the lines in place of the points, so a key with other lines
+    /// would verify openings against another SRS.
+    pub(crate) fn check_lines(&self) -> Result<(), Error> {
+        if Self::new(self.g1, self.g2)?.lines != self.lines {
+            return Err(VkError::InvalidPairingLines.into());
+        }
 
-#[derive(Clone, Copy, Debug)]
-#[allow(dead_code)]
-pub(crate) struct E2 {
-    pub(crate) a0: Fr,
-    pub(crate) a1: Fr,
-}
+        Ok(())
+    }
 
-#[derive(Clone, Copy, Debug)]
-#[allow(dead_code)]
-pub(crate) struct LineEvaluationAff {
-    pub(crate) r0: E2,
-    pub(crate) r1: E2,
+    /// Returns the G1 generator of the SRS.
+    pub fn g1(&self) -> AffineG1 {
+        self.g1
+    }
+
+    /// Returns [G₂, [α]G₂] from the SRS.
+    pub fn g2(&self) -> [AffineG2; 2] {
+        self.g2
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
-#[allow(dead_code)]
-pub(crate) struct KZGVerifyingKey {
-    pub(crate) g2: [G2; 2], // [G₂, [α]G₂]
-    pub(crate) g1: G1,
-    // Precomputed pairing lines corresponding to G₂, [α]G₂
-    pub(crate) lines: [[[LineEvaluationAff; 66]; 2]; 2],
+impl fmt::Debug for KZGVerifyingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KZGVerifyingKey")
+            .field("g2", &[G2::from(self.g2[0]), G2::from(self.g2[1])])
+            .field("g1", &self.g1)
+            .finish_non_exhaustive()
+    }
 }
 
+/// A proof that several polynomials open to the claimed values at the same point.
 #[derive(Clone, Debug)]
-pub(crate) struct BatchOpeningProof {
+pub struct BatchOpeningProof {
     pub(crate) h: AffineG1,
     pub(crate) claimed_values: Vec<Fr>,
 }
 
+impl BatchOpeningProof {
+    /// Creates a batch opening proof from the commitment to the quotient of the folded
+    /// polynomial and the value claimed for each polynomial.
+    pub fn new(h: AffineG1, claimed_values: Vec<Fr>) -> Self {
+        Self { h, claimed_values }
+    }
+
+    /// Returns the commitment to the quotient of the folded polynomial.
+    pub fn h(&self) -> AffineG1 {
+        self.h
+    }
+
+    /// Returns the value claimed for each polynomial.
+    pub fn claimed_values(&self) -> &[Fr] {
+        &self.claimed_values
+    }
+}
+
+/// A proof that a polynomial opens to the claimed value at a point.
 #[derive(Clone, Copy, Debug)]
-pub(crate) struct OpeningProof {
+pub struct OpeningProof {
     pub(crate) h: AffineG1,
     pub(crate) claimed_value: Fr,
 }
 
-fn derive_gamma(
+impl OpeningProof {
+    /// Creates an opening proof from the commitment to the quotient polynomial and the
+    /// claimed value.
+    pub fn new(h: AffineG1, claimed_value: Fr) -> Self {
+        Self { h, claimed_value }
+    }
+
+    /// Returns the commitment to the quotient polynomial.
+    pub fn h(&self) -> AffineG1 {
+        self.h
+    }
+
+    /// Returns the claimed value.
+    pub fn claimed_value(&self) -> Fr {
+        self.claimed_value
+    }
+}
+
+fn derive_gamma<H: FiatShamirHash>(
     point: &Fr,
     digests: Vec<Digest>,
     claimed_values: Vec<Fr>,
     data_transcript: Option<Vec<u8>>,
-) -> Result<Fr, PlonkError> {
-    let mut transcript = Transcript::new(Some([GAMMA.to_string()].to_vec()))?;
+) -> Result<Fr, Error> {
+    let mut transcript = Transcript::<H>::new(&[GAMMA])?;
     transcript.bind(GAMMA, &point.into_u256().to_bytes_be())?;
 
     for digest in digests.iter() {
@@ -65,13 +169,12 @@ fn derive_gamma(
     }
 
     let gamma_byte = transcript.compute_challenge(GAMMA)?;
-    let x = Fr::from_bytes_be_mod_order(gamma_byte.as_slice())
-        .map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
+    let x = Fr::from_bytes_be_mod_order(&gamma_byte).map_err(InternalError::Field)?;
 
     Ok(x)
 }
 
-fn fold(di: Vec<Digest>, fai: Vec<Fr>, ci: Vec<Fr>) -> Result<(AffineG1, Fr), PlonkError> {
+fn fold(di: Vec<Digest>, fai: Vec<Fr>, ci: Vec<Fr>) -> Result<(AffineG1, Fr), Error> {
     let nb_digests = di.len();
     let mut folded_evaluations = Fr::zero();
 
@@ -84,19 +187,20 @@ fn fold(di: Vec<Digest>, fai: Vec<Fr>, ci: Vec<Fr>) -> Result<(AffineG1, Fr), Pl
     Ok((folded_digests, folded_evaluations))
 }
 
-pub(crate) fn fold_proof(
+// Folds the openings with the powers of a challenge derived with H, gnark's KZG folding hash
+pub(crate) fn fold_proof<H: FiatShamirHash>(
     digests: Vec<Digest>,
     batch_opening_proof: &BatchOpeningProof,
     point: &Fr,
     data_transcript: Option<Vec<u8>>,
-) -> Result<(OpeningProof, AffineG1), PlonkError> {
+) -> Result<(OpeningProof, AffineG1), Error> {
     let nb_digests = digests.len();
 
-    if nb_digests != batch_opening_proof.claimed_values.len() {
-        return Err(Error::InvalidNumberOfDigests.into());
+    if nb_digests == 0 || nb_digests != batch_opening_proof.claimed_values.len() {
+        return Err(ProofCheck::NumberOfDigests.into());
     }
 
-    let gamma = derive_gamma(
+    let gamma = derive_gamma::<H>(
         point,
         digests.clone(),
         batch_opening_proof.claimed_values.clone(),
@@ -125,35 +229,161 @@ pub(crate) fn fold_proof(
     Ok((open_proof, folded_digests))
 }
 
-pub(crate) fn batch_verify_multi_points(
-    digests: Vec<Digest>,
-    proofs: Vec<OpeningProof>,
-    points: Vec<Fr>,
+/// Verifies that the polynomial committed to in `commitment` evaluates to the claimed value of
+/// `proof` at `point`.
+///
+/// # Returns
+///
+/// `Ok(())` if the opening is valid, or an [`Error::ProofRejected`] if the pairing check fails.
+pub fn verify(
+    commitment: &Digest,
+    proof: &OpeningProof,
+    point: &Fr,
     vk: &KZGVerifyingKey,
-) -> Result<(), PlonkError> {
-    let nb_digests = digests.len();
-    let nb_proofs = proofs.len();
-    let nb_points = points.len();
+) -> Result<(), Error> {
+    // [f(α) - f(a) + a*H(α)]G₁
+    let total_g1 = *commitment - vk.g1 * proof.claimed_value + proof.h * *point;
+
+    // e([f(α) - f(a) + a*H(α)]G₁, G₂) * e([-H(α)]G₁, [α]G₂) == 1
+    if !pairing_check_fixed_q(&[(total_g1, &vk.lines[0]), (-proof.h, &vk.lines[1])]) {
+        return Err(ProofCheck::Pairing.into());
+    }
+
+    Ok(())
+}
+
+/// Verifies a batch opening proof of several polynomials at the same point.
+///
+/// The polynomials are folded with powers of a challenge derived with `H` from the point, the
+/// digests, the claimed values and `data_transcript`, then the folded opening is checked with
+/// [`verify`]. `H` is the hash the prover folded with, gnark's `hf` argument.
+///
+/// # Returns
+///
+/// `Ok(())` if the openings are valid, or an [`Error::ProofRejected`] if there are no digests,
+/// the number of digests does not match the number of claimed values or the pairing check
+/// fails.
+pub fn batch_verify_single_point<H: FiatShamirHash>(
+    digests: &[Digest],
+    batch_opening_proof: &BatchOpeningProof,
+    point: &Fr,
+    data_transcript: Option<&[u8]>,
+    vk: &KZGVerifyingKey,
+) -> Result<(), Error> {
+    let (folded_proof, folded_digest) = fold_proof::<H>(
+        digests.to_vec(),
+        batch_opening_proof,
+        point,
+        data_transcript.map(|data| data.to_vec()),
+    )?;
+
+    verify(&folded_digest, &folded_proof, point, vk)
+}
+
+/// Verifies openings of several polynomials, each at its own point.
+///
+/// The openings are combined with the powers of a challenge λ derived by Fiat-Shamir from the
+/// digests, the proofs and the points, so that they are checked with a single pairing.
+///
+/// # Returns
+///
+/// `Ok(())` if the openings are valid, or an [`Error::ProofRejected`] if the numbers of digests,
+/// proofs and points differ or the pairing check fails.
+pub fn batch_verify_multi_points(
+    digests: &[Digest],
+    proofs: &[OpeningProof],
+    points: &[Fr],
+    vk: &KZGVerifyingKey,
+) -> Result<(), Error> {
+    check_multi_points_lengths(digests, proofs, points)?;
+
+    if digests.len() == 1 {
+        return verify(&digests[0], &proofs[0], &points[0], vk);
+    }
+
+    let lambda = derive_lambda(digests, proofs, points)?;
+    let mut coefficients = Vec::with_capacity(digests.len());
+    coefficients.push(Fr::one());
+    for i in 1..digests.len() {
+        coefficients.push(coefficients[i - 1] * lambda);
+    }
+
+    verify_combined_openings(digests, proofs, points, coefficients, vk)
+}
 
-    if nb_digests != nb_proofs {
-        return Err(Error::InvalidNumberOfDigests.into());
+/// Verifies openings of several polynomials, each at its own point, combining them with
+/// coefficients drawn from `rng` instead of derived by Fiat-Shamir.
+///
+/// See [`batch_verify_multi_points`].
+#[cfg(feature = "rand")]
+pub fn batch_verify_multi_points_with_rng<R: rand::Rng>(
+    digests: &[Digest],
+    proofs: &[OpeningProof],
+    points: &[Fr],
+    vk: &KZGVerifyingKey,
+    rng: &mut R,
+) -> Result<(), Error> {
+    check_multi_points_lengths(digests, proofs, points)?;
+
+    if digests.len() == 1 {
+        return verify(&digests[0], &proofs[0], &points[0], vk);
     }
 
-    if nb_digests != nb_points {
-        return Err(Error::InvalidNumberOfDigests.into());
+    let mut coefficients = Vec::with_capacity(digests.len());
+    coefficients.push(Fr::one());
+    for _ in 1..digests.len() {
+        coefficients.push(Fr::random(rng));
     }
 
-    if nb_digests == 1 {
-        todo!();
+    verify_combined_openings(digests, proofs, points, coefficients, vk)
+}
+
+fn check_multi_points_lengths(
+    digests: &[Digest],
+    proofs: &[OpeningProof],
+    points: &[Fr],
+) -> Result<(), Error> {
+    let nb_digests = digests.len();
+
+    if nb_digests == 0 || nb_digests != proofs.len() || nb_digests != points.len() {
+        return Err(ProofCheck::NumberOfDigests.into());
     }
-    let mut rng = OsRng;
-    let mut random_numbers = Vec::with_capacity(nb_digests);
-    random_numbers.push(Fr::one());
-    for _ in 1..nb_digests {
-        random_numbers.push(Fr::random(&mut rng));
+
+    Ok(())
+}
+
+// Derive λ from everything the combined check depends on, so that a prover cannot choose the
+// openings after the coefficients.
+fn derive_lambda(digests: &[Digest], proofs: &[OpeningProof], points: &[Fr]) -> Result<Fr, Error> {
+    let mut transcript = Transcript::<Sha256>::new(&[KZG_BATCH_CHALLENGE])?;
+
+    for ((digest, proof), point) in digests.iter().zip(proofs.iter()).zip(points.iter()) {
+        transcript.bind(KZG_BATCH_CHALLENGE, &g1_to_bytes(digest)?)?;
+        transcript.bind(KZG_BATCH_CHALLENGE, &g1_to_bytes(&proof.h)?)?;
+        transcript.bind(
+            KZG_BATCH_CHALLENGE,
+            &proof.claimed_value.into_u256().to_bytes_be(),
+        )?;
+        transcript.bind(KZG_BATCH_CHALLENGE, &point.into_u256().to_bytes_be())?;
     }
 
-    let mut quotients = Vec::with_capacity(nb_proofs);
+    let lambda_bytes = transcript.compute_challenge(KZG_BATCH_CHALLENGE)?;
+    let lambda = Fr::from_bytes_be_mod_order(&lambda_bytes).map_err(InternalError::Field)?;
+
+    Ok(lambda)
+}
+
+// e(∑ rᵢ * ([fᵢ(α)]G₁ - [fᵢ(aᵢ)]G₁ + aᵢ * [Hᵢ(α)]G₁), G₂) * e(-∑ rᵢ * [Hᵢ(α)]G₁, [α]G₂) == 1
+fn verify_combined_openings(
+    digests: &[Digest],
+    proofs: &[OpeningProof],
+    points: &[Fr],
+    mut random_numbers: Vec<Fr>,
+    vk: &KZGVerifyingKey,
+) -> Result<(), Error> {
+    let nb_digests = digests.len();
+
+    let mut quotients = Vec::with_capacity(nb_digests);
     for item in proofs.iter().take(nb_digests) {
         quotients.push(item.h);
     }
@@ -165,9 +395,9 @@ pub(crate) fn batch_verify_multi_points(
         evals.push(item.claimed_value);
     }
 
-    let (mut folded_digests, folded_evals) = fold(digests, evals, random_numbers.clone())?;
+    let (mut folded_digests, folded_evals) = fold(digests.to_vec(), evals, random_numbers.clone())?;
     let folded_evals_commit = vk.g1 * folded_evals;
-    folded_digests = folded_digests - folded_evals_commit.into();
+    folded_digests = folded_digests - folded_evals_commit;
 
     for i in 0..random_numbers.len() {
         random_numbers[i] *= points[i];
@@ -177,14 +407,153 @@ pub(crate) fn batch_verify_multi_points(
     folded_digests = folded_digests + folded_points_quotients;
     folded_quotients = -folded_quotients;
 
-    let pairing_result = pairing_batch(&[
-        (folded_digests.into(), vk.g2[0]),
-        (folded_quotients.into(), vk.g2[1]),
-    ]);
-
-    if !pairing_result.is_one() {
-        return Err(Error::PairingCheckFailed.into());
+    if !pairing_check_fixed_q(&[
+        (folded_digests, &vk.lines[0]),
+        (folded_quotients, &vk.lines[1]),
+    ]) {
+        return Err(ProofCheck::Pairing.into());
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::g2_identity;
+    use bn::{Fq2, Group, G1};
+    use sha3::Keccak256;
+
+    fn srs() -> (AffineG1, [AffineG2; 2]) {
+        let alpha = Fr::from_str("12345").expect("valid scalar");
+        let g2 = AffineG2::one();
+        let alpha_g2 = AffineG2::from_jacobian(G2::one() * alpha).expect("non-zero scalar");
+
+        (AffineG1::one(), [g2, alpha_g2])
+    }
+
+    #[test]
+    fn test_new_rejects_identity() {
+        let (g1, [g2, alpha_g2]) = srs();
+
+        for g2 in [[g2_identity(), alpha_g2], [g2, g2_identity()]] {
+            assert!(matches!(
+                KZGVerifyingKey::new(g1, g2),
+                Err(Error::InvalidVerifyingKey(VkError::IdentityPoint("kzg_g2")))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_check_lines_accepts_precomputed_lines() {
+        let (g1, g2) = srs();
+        let vk = KZGVerifyingKey::new(g1, g2).unwrap();
+
+        assert!(vk.check_lines().is_ok());
+        assert!(KZGVerifyingKey::with_lines(g1, g2, vk.lines)
+            .unwrap()
+            .check_lines()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_lines_rejects_any_tampered_line() {
+        let (g1, g2) = srs();
+        let vk = KZGVerifyingKey::new(g1, g2).unwrap();
+
+        // The tangent at the point, and lines the Miller loop only reaches later
+        for (point, kind, index) in [(0, 0, LOOP_LENGTH - 2), (0, 0, 0), (1, 1, 3), (1, 1, 65)] {
+            let mut lines = vk.lines;
+            lines[point][kind][index].r1 = lines[point][kind][index].r1 + Fq2::one();
+
+            // Trusted as they are, until checked
+            let tampered = KZGVerifyingKey::with_lines(g1, g2, lines).unwrap();
+            assert!(tampered.lines == lines);
+            assert!(matches!(
+                tampered.check_lines(),
+                Err(Error::InvalidVerifyingKey(VkError::InvalidPairingLines))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_check_lines_rejects_lines_of_other_points() {
+        let (g1, g2) = srs();
+        let vk = KZGVerifyingKey::new(g1, g2).unwrap();
+
+        let swapped = KZGVerifyingKey::with_lines(g1, g2, [vk.lines[1], vk.lines[0]]).unwrap();
+        assert!(swapped.check_lines().is_err());
+    }
+
+    #[test]
+    fn test_fold_proof_rejects_empty_openings() {
+        let proof = BatchOpeningProof::new(AffineG1::one(), vec![]);
+
+        assert!(matches!(
+            fold_proof::<Sha256>(vec![], &proof, &Fr::one(), None),
+            Err(Error::ProofRejected(ProofCheck::NumberOfDigests))
+        ));
+    }
+
+    #[test]
+    fn test_fold_proof_rejects_mismatched_claimed_values() {
+        let proof = BatchOpeningProof::new(AffineG1::one(), vec![Fr::one(), Fr::one()]);
+
+        assert!(matches!(
+            fold_proof::<Sha256>(vec![AffineG1::one()], &proof, &Fr::one(), None),
+            Err(Error::ProofRejected(ProofCheck::NumberOfDigests))
+        ));
+    }
+
+    #[test]
+    fn test_verify_opening_of_constant_polynomial() {
+        let (g1, g2) = srs();
+        let vk = KZGVerifyingKey::new(g1, g2).unwrap();
+
+        // f = c commits to [c]G₁ and opens to c anywhere, with a zero quotient
+        let c = Fr::from_str("7").unwrap();
+        let commitment = AffineG1::from_jacobian(G1::one() * c).unwrap();
+        let point = Fr::from_str("3").unwrap();
+
+        let proof = OpeningProof::new(AffineG1::default(), c);
+        assert!(verify(&commitment, &proof, &point, &vk).is_ok());
+
+        let proof = OpeningProof::new(AffineG1::default(), c + Fr::one());
+        assert!(matches!(
+            verify(&commitment, &proof, &point, &vk),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+
+    #[test]
+    fn test_batch_verify_single_point_with_hash() {
+        let (g1, g2) = srs();
+        let vk = KZGVerifyingKey::new(g1, g2).unwrap();
+        let alpha = Fr::from_str("12345").unwrap();
+        let point = Fr::from_str("3").unwrap();
+
+        // fᵢ = sᵢ * X + cᵢ commits to [sᵢ * α + cᵢ]G₁, opens to sᵢ * a + cᵢ at a, with the
+        // constant quotient sᵢ
+        let fr = |n: &str| Fr::from_str(n).unwrap();
+        let (s, c) = ([fr("5"), fr("11")], [fr("7"), fr("13")]);
+        let digests: Vec<Digest> = (0..2)
+            .map(|i| AffineG1::from_jacobian(G1::one() * (s[i] * alpha + c[i])).unwrap())
+            .collect();
+        let claimed_values: Vec<Fr> = (0..2).map(|i| s[i] * point + c[i]).collect();
+
+        // The quotients are folded with the challenge γ derived with Keccak-256
+        let gamma =
+            derive_gamma::<Keccak256>(&point, digests.clone(), claimed_values.clone(), None)
+                .unwrap();
+        let h = AffineG1::from_jacobian(G1::one() * (s[0] + gamma * s[1])).unwrap();
+        let proof = BatchOpeningProof::new(h, claimed_values);
+
+        assert!(
+            batch_verify_single_point::<Keccak256>(&digests, &proof, &point, None, &vk).is_ok()
+        );
+        assert!(matches!(
+            batch_verify_single_point::<Sha256>(&digests, &proof, &point, None, &vk),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+}
@@ -1,10 +1,12 @@
 mod converter;
-mod kzg;
 mod proof;
 mod verify;
+mod version;
 
-pub(crate) mod error;
+pub mod kzg;
 
 pub(crate) use converter::{load_plonk_proof_from_bytes, load_plonk_verifying_key_from_bytes};
-pub(crate) use proof::PlonkProof;
-pub(crate) use verify::verify_plonk;
+pub use proof::PlonkProof;
+pub(crate) use verify::{prepare_plonk_verifying_key, verify_plonk, verify_plonk_batch};
+pub use verify::{PlonkVerifyingKey, PreparedPlonkVerifyingKey};
+pub use version::GnarkVersion;
@@ -1,13 +1,178 @@
 use alloc::vec::Vec;
+use bn::{AffineG1, Fr};
 
-use super::kzg::{BatchOpeningProof, Digest, OpeningProof};
+use crate::{
+    converter::PointEncoding,
+    error::{Error, ProofCheck, VkError},
+};
 
-#[derive(Debug)]
-pub(crate) struct PlonkProof {
+use super::{
+    kzg::{BatchOpeningProof, Digest, OpeningProof},
+    PlonkVerifyingKey,
+};
+
+// The claimed values of the batched opening that do not depend on the BSB22 commitments: the
+// linearized polynomial, l, r, o, s1 and s2
+const NB_FIXED_CLAIMED_VALUES: usize = 6;
+
+/// A PLONK proof, as serialized by gnark.
+#[derive(Clone, Debug)]
+pub struct PlonkProof {
     pub(crate) lro: [Digest; 3],
     pub(crate) z: Digest,
     pub(crate) h: [Digest; 3],
     pub(crate) bsb22_commitments: Vec<Digest>,
     pub(crate) batched_proof: BatchOpeningProof,
     pub(crate) z_shifted_opening: OpeningProof,
+    pub(crate) encoding: PointEncoding,
+}
+
+impl PlonkProof {
+    /// Creates a proof from its commitments and openings.
+    ///
+    /// The proof is serialized with uncompressed points, as gnark's `WriteRawTo` does, unless
+    /// another encoding is set with [`Self::with_encoding`].
+    ///
+    /// # Arguments
+    ///
+    /// * `lro` - The commitments to the wire polynomials l, r and o
+    /// * `z` - The commitment to the permutation accumulator
+    /// * `h` - The commitments to the three parts of the quotient polynomial
+    /// * `bsb22_commitments` - The BSB22 commitments, empty if the circuit has none
+    /// * `batched_proof_h` - The quotient of the batched opening at ζ
+    /// * `claimed_values` - The values claimed by the batched opening at ζ
+    /// * `z_shifted_opening_h` - The quotient of the opening of z at ωζ
+    /// * `z_shifted_opening_value` - The value of z at ωζ
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_parts(
+        lro: [AffineG1; 3],
+        z: AffineG1,
+        h: [AffineG1; 3],
+        bsb22_commitments: Vec<AffineG1>,
+        batched_proof_h: AffineG1,
+        claimed_values: Vec<Fr>,
+        z_shifted_opening_h: AffineG1,
+        z_shifted_opening_value: Fr,
+    ) -> Self {
+        Self {
+            lro,
+            z,
+            h,
+            bsb22_commitments,
+            batched_proof: BatchOpeningProof {
+                h: batched_proof_h,
+                claimed_values,
+            },
+            z_shifted_opening: OpeningProof {
+                h: z_shifted_opening_h,
+                claimed_value: z_shifted_opening_value,
+            },
+            encoding: PointEncoding::Uncompressed,
+        }
+    }
+
+    /// Sets the point encoding used by [`Self::to_gnark_bytes`].
+    pub fn with_encoding(mut self, encoding: PointEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the commitments to the wire polynomials l, r and o.
+    pub fn lro(&self) -> &[AffineG1; 3] {
+        &self.lro
+    }
+
+    /// Returns the commitment to the permutation accumulator.
+    pub fn z(&self) -> AffineG1 {
+        self.z
+    }
+
+    /// Returns the commitments to the three parts of the quotient polynomial.
+    pub fn h(&self) -> &[AffineG1; 3] {
+        &self.h
+    }
+
+    /// Returns the BSB22 commitments.
+    pub fn bsb22_commitments(&self) -> &[AffineG1] {
+        &self.bsb22_commitments
+    }
+
+    /// Returns the quotient of the batched opening at ζ.
+    pub fn batched_proof_h(&self) -> AffineG1 {
+        self.batched_proof.h
+    }
+
+    /// Returns the values claimed by the batched opening at ζ.
+    pub fn claimed_values(&self) -> &[Fr] {
+        &self.batched_proof.claimed_values
+    }
+
+    /// Returns the quotient of the opening of z at ωζ.
+    pub fn z_shifted_opening_h(&self) -> AffineG1 {
+        self.z_shifted_opening.h
+    }
+
+    /// Returns the value of z at ωζ.
+    pub fn z_shifted_opening_value(&self) -> Fr {
+        self.z_shifted_opening.claimed_value
+    }
+
+    /// Returns the point encoding of the serialized proof.
+    pub fn encoding(&self) -> PointEncoding {
+        self.encoding
+    }
+
+    /// Checks that the shape of the proof matches the verifying key and the public inputs,
+    /// before any of them is used in the verification.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the proof has one BSB22 commitment per commitment selector and commitment
+    /// constraint index of `vk`, one claimed value per polynomial opened at ζ, and if there
+    /// are as many public inputs as public variables in `vk`. Otherwise, an
+    /// [`Error::ProofRejected`] describing the first mismatch, or an
+    /// [`Error::InvalidVerifyingKey`] if `vk` has not one commitment constraint index per
+    /// commitment selector.
+    pub fn validate_against(
+        &self,
+        vk: &PlonkVerifyingKey,
+        public_inputs: &[Fr],
+    ) -> Result<(), Error> {
+        let nb_commitments = self.bsb22_commitments.len();
+
+        if nb_commitments != vk.qcp.len() {
+            return Err(ProofCheck::NumberOfBsb22Commitments {
+                expected: vk.qcp.len(),
+                got: nb_commitments,
+            }
+            .into());
+        }
+
+        if vk.commitment_constraint_indexes.len() != nb_commitments {
+            return Err(VkError::InvalidNumberOfCommitmentIndexes {
+                expected: nb_commitments,
+                got: vk.commitment_constraint_indexes.len(),
+            }
+            .into());
+        }
+
+        let nb_claimed_values = NB_FIXED_CLAIMED_VALUES + vk.qcp.len();
+        if self.batched_proof.claimed_values.len() != nb_claimed_values {
+            return Err(ProofCheck::NumberOfClaimedValues {
+                expected: nb_claimed_values,
+                got: self.batched_proof.claimed_values.len(),
+            }
+            .into());
+        }
+
+        if public_inputs.len() != vk.nb_public_variables {
+            return Err(ProofCheck::NumberOfPublicInputs {
+                expected: vk.nb_public_variables,
+                got: public_inputs.len(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
 }
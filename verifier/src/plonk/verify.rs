@@ -1,16 +1,20 @@
-use alloc::{string::ToString, vec, vec::Vec};
-use bn::{arith::U256, AffineG1, Fr};
-use core::hash::Hasher;
+use alloc::{vec, vec::Vec};
+use bn::{arith::U256, AffineG1, AffineG2, Fr};
+use core::{fmt, marker::PhantomData};
+use sha2::Sha256;
 
 use crate::{
-    constants::{ALPHA, BETA, GAMMA, ZETA},
-    error::Error,
+    constants::{ALPHA, BETA, GAMMA, PLONK_CHALLENGES, PLONK_COMMITMENT_DST, ZETA},
+    converter::{g1_to_bytes, PointEncoding},
+    error::{Error, InternalError, ProofCheck, VkError},
+    hash_to_field::{FiatShamirHash, HashToField, WrappedHashToField},
     transcript::Transcript,
 };
 
-use super::{converter::g1_to_bytes, error::PlonkError, kzg, PlonkProof};
-#[derive(Debug)]
-pub(crate) struct PlonkVerifyingKey {
+use super::{kzg, GnarkVersion, PlonkProof};
+/// A PLONK verifying key, as serialized by gnark.
+#[derive(Clone)]
+pub struct PlonkVerifyingKey {
     pub(crate) size: usize,
     pub(crate) size_inv: Fr,
     pub(crate) generator: Fr,
@@ -30,6 +34,367 @@ pub(crate) struct PlonkVerifyingKey {
     pub(crate) qcp: Vec<kzg::Digest>,
 
     pub(crate) commitment_constraint_indexes: Vec<usize>,
+
+    // The gnark release whose layout the key is serialized in
+    pub(crate) version: GnarkVersion,
+    pub(crate) encoding: PointEncoding,
+}
+
+impl PlonkVerifyingKey {
+    /// Creates a verifying key from its parts, as they appear in gnark's `VerifyingKey`.
+    ///
+    /// The pairing lines of the KZG verifying key are precomputed from `kzg_g2`, and the key is
+    /// serialized in the layout of the latest supported gnark release, with compressed points
+    /// as gnark's `WriteTo` does, unless set otherwise with [`Self::with_version`] and
+    /// [`Self::with_encoding`].
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The size of the evaluation domain
+    /// * `size_inv` - The inverse of `size` in the scalar field
+    /// * `generator` - The generator ω of the evaluation domain
+    /// * `nb_public_variables` - The number of public inputs
+    /// * `coset_shift` - The shift of the coset used for the quotient polynomial
+    /// * `s` - The commitments to the permutation polynomials
+    /// * `ql`, `qr`, `qm`, `qo`, `qk` - The commitments to the selector polynomials
+    /// * `qcp` - The commitments to the BSB22 commitment selector polynomials
+    /// * `commitment_constraint_indexes` - The constraint index of each BSB22 commitment
+    /// * `kzg_g1` - The G1 generator of the KZG SRS
+    /// * `kzg_g2` - [G₂, [α]G₂] from the KZG SRS
+    ///
+    /// # Returns
+    ///
+    /// The verifying key, or an [`Error::InvalidVerifyingKey`] if a point of `kzg_g2` is the
+    /// identity.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_parts(
+        size: usize,
+        size_inv: Fr,
+        generator: Fr,
+        nb_public_variables: usize,
+        coset_shift: Fr,
+        s: [AffineG1; 3],
+        ql: AffineG1,
+        qr: AffineG1,
+        qm: AffineG1,
+        qo: AffineG1,
+        qk: AffineG1,
+        qcp: Vec<AffineG1>,
+        commitment_constraint_indexes: Vec<usize>,
+        kzg_g1: AffineG1,
+        kzg_g2: [AffineG2; 2],
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            size,
+            size_inv,
+            generator,
+            nb_public_variables,
+            kzg: kzg::KZGVerifyingKey::new(kzg_g1, kzg_g2)?,
+            coset_shift,
+            s,
+            ql,
+            qr,
+            qm,
+            qo,
+            qk,
+            qcp,
+            commitment_constraint_indexes,
+            version: GnarkVersion::default(),
+            encoding: PointEncoding::Compressed,
+        })
+    }
+
+    /// Sets the point encoding used by [`Self::to_gnark_bytes`].
+    pub fn with_encoding(mut self, encoding: PointEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the gnark release whose layout [`Self::to_gnark_bytes`] follows.
+    pub fn with_version(mut self, version: GnarkVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns the size of the evaluation domain.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the inverse of the domain size in the scalar field.
+    pub fn size_inv(&self) -> Fr {
+        self.size_inv
+    }
+
+    /// Returns the generator ω of the evaluation domain.
+    pub fn generator(&self) -> Fr {
+        self.generator
+    }
+
+    /// Returns the number of public inputs.
+    pub fn nb_public_variables(&self) -> usize {
+        self.nb_public_variables
+    }
+
+    /// Returns the shift of the coset used for the quotient polynomial.
+    pub fn coset_shift(&self) -> Fr {
+        self.coset_shift
+    }
+
+    /// Returns the commitments to the permutation polynomials.
+    pub fn s(&self) -> &[AffineG1; 3] {
+        &self.s
+    }
+
+    /// Returns the commitment to the left selector polynomial.
+    pub fn ql(&self) -> AffineG1 {
+        self.ql
+    }
+
+    /// Returns the commitment to the right selector polynomial.
+    pub fn qr(&self) -> AffineG1 {
+        self.qr
+    }
+
+    /// Returns the commitment to the multiplication selector polynomial.
+    pub fn qm(&self) -> AffineG1 {
+        self.qm
+    }
+
+    /// Returns the commitment to the output selector polynomial.
+    pub fn qo(&self) -> AffineG1 {
+        self.qo
+    }
+
+    /// Returns the commitment to the constant selector polynomial.
+    pub fn qk(&self) -> AffineG1 {
+        self.qk
+    }
+
+    /// Returns the commitments to the BSB22 commitment selector polynomials.
+    pub fn qcp(&self) -> &[AffineG1] {
+        &self.qcp
+    }
+
+    /// Returns the constraint index of each BSB22 commitment.
+    pub fn commitment_constraint_indexes(&self) -> &[usize] {
+        &self.commitment_constraint_indexes
+    }
+
+    /// Returns the G1 generator of the KZG SRS.
+    pub fn kzg_g1(&self) -> AffineG1 {
+        self.kzg.g1
+    }
+
+    /// Returns [G₂, [α]G₂] from the KZG SRS.
+    pub fn kzg_g2(&self) -> [AffineG2; 2] {
+        self.kzg.g2
+    }
+
+    /// Returns the gnark release whose layout the key is serialized in.
+    pub fn version(&self) -> GnarkVersion {
+        self.version
+    }
+
+    /// Returns the point encoding of the serialized key.
+    pub fn encoding(&self) -> PointEncoding {
+        self.encoding
+    }
+
+    /// Checks that the parts of the verifying key are consistent with each other, as they are
+    /// in a key set up by gnark.
+    ///
+    /// The points are not checked, see [`VkValidation`](crate::VkValidation) for that.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the domain size is a power of two whose inverse is `size_inv`, if
+    /// `generator` has order exactly `size`, if neither `coset_shift` nor its square is in the
+    /// domain, if every BSB22 commitment constraint, placed after the public inputs, is in the
+    /// domain, and if there is one commitment selector per commitment constraint index.
+    /// Otherwise, an [`Error::InvalidVerifyingKey`] describing the first failed check.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !self.size.is_power_of_two() {
+            return Err(VkError::InvalidDomainSize(self.size).into());
+        }
+
+        let size = fr_from_usize(self.size)?;
+        if self.size_inv * size != Fr::one() {
+            return Err(VkError::InvalidDomainSizeInverse.into());
+        }
+
+        // Once ω^size = 1, the order of ω divides the size, a power of two, so it is the size
+        // unless ω^(size / 2) is already 1
+        if self.generator.pow(size) != Fr::one()
+            || (self.size > 1 && self.generator.pow(fr_from_usize(self.size / 2)?) == Fr::one())
+        {
+            return Err(VkError::InvalidDomainGenerator.into());
+        }
+
+        // x is in the domain if and only if x^size = 1
+        let shift_pow = self.coset_shift.pow(size);
+        if shift_pow == Fr::one() || shift_pow * shift_pow == Fr::one() {
+            return Err(VkError::InvalidCosetShift.into());
+        }
+
+        for index in &self.commitment_constraint_indexes {
+            match self.nb_public_variables.checked_add(*index) {
+                Some(position) if position < self.size => {}
+                _ => {
+                    return Err(VkError::CommitmentIndexOutOfDomain {
+                        index: *index,
+                        size: self.size,
+                    }
+                    .into())
+                }
+            }
+        }
+
+        if self.qcp.len() != self.commitment_constraint_indexes.len() {
+            return Err(VkError::InvalidNumberOfCommitmentIndexes {
+                expected: self.qcp.len(),
+                got: self.commitment_constraint_indexes.len(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+fn fr_from_usize(value: usize) -> Result<Fr, Error> {
+    Fr::from_slice(&U256::from(value as u64).to_bytes_be())
+        .map_err(|e| InternalError::Field(e).into())
+}
+
+impl fmt::Debug for PlonkVerifyingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PlonkVerifyingKey")
+            .field("size", &self.size)
+            .field("size_inv", &self.size_inv)
+            .field("generator", &self.generator)
+            .field("nb_public_variables", &self.nb_public_variables)
+            .field("kzg", &self.kzg)
+            .field("coset_shift", &self.coset_shift)
+            .field("s", &self.s)
+            .field("ql", &self.ql)
+            .field("qr", &self.qr)
+            .field("qm", &self.qm)
+            .field("qo", &self.qo)
+            .field("qk", &self.qk)
+            .field("qcp", &self.qcp)
+            .field(
+                "commitment_constraint_indexes",
+                &self.commitment_constraint_indexes,
+            )
+            .field("version", &self.version)
+            .field("encoding", &self.encoding)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A PLONK verifying key together with the values every verification against it recomputes
+///
+/// Holds the transcript state after the verifying key is bound to the first challenge, the
+/// domain size in the scalar field, and the powers of ω at which the public inputs and the BSB22
+/// commitments are evaluated.
+///
+/// The Fiat-Shamir challenges and the KZG folding challenge are derived with `C`, and the BSB22
+/// commitments are hashed with `F` under the domain separator set with
+/// [`Self::with_bsb22_dst`], as gnark's `ChallengeHash`, `KZGFoldingHash` and `HashToFieldFn`
+/// verifier options.
+#[derive(Clone)]
+pub struct PreparedPlonkVerifyingKey<C = Sha256, F = WrappedHashToField> {
+    pub(crate) vk: PlonkVerifyingKey,
+    pub(crate) gamma_prefix: C,
+    pub(crate) size: Fr,
+    // ωⁱ for each public input i
+    pub(crate) public_input_omegas: Vec<Fr>,
+    // ω^(nb_public_variables + index) for each BSB22 commitment constraint index
+    pub(crate) commitment_omegas: Vec<Fr>,
+    pub(crate) bsb22_dst: Vec<u8>,
+    pub(crate) hash_to_field: PhantomData<F>,
+}
+
+impl<C, F> PreparedPlonkVerifyingKey<C, F> {
+    /// Returns the verifying key this was prepared from.
+    pub fn vk(&self) -> &PlonkVerifyingKey {
+        &self.vk
+    }
+
+    /// Sets the domain separator the BSB22 commitments are hashed under, `BSB22-Plonk` by
+    /// default.
+    pub fn with_bsb22_dst(mut self, dst: &[u8]) -> Self {
+        self.bsb22_dst = dst.to_vec();
+        self
+    }
+
+    /// Returns the domain separator the BSB22 commitments are hashed under.
+    pub fn bsb22_dst(&self) -> &[u8] {
+        &self.bsb22_dst
+    }
+}
+
+impl<C, F> fmt::Debug for PreparedPlonkVerifyingKey<C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedPlonkVerifyingKey")
+            .field("vk", &self.vk)
+            .field("size", &self.size)
+            .field("public_input_omegas", &self.public_input_omegas)
+            .field("commitment_omegas", &self.commitment_omegas)
+            .field("bsb22_dst", &self.bsb22_dst)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Prepares a PLONK verifying key for verification
+///
+/// # Arguments
+///
+/// * `vk` - The verifying key
+///
+/// # Returns
+///
+/// * `Result<PreparedPlonkVerifyingKey<C, F>, Error>` - The prepared verifying key, or an
+///   error if the key cannot be bound to the transcript
+pub fn prepare_plonk_verifying_key<C: FiatShamirHash, F: HashToField>(
+    vk: PlonkVerifyingKey,
+) -> Result<PreparedPlonkVerifyingKey<C, F>, Error> {
+    let mut fs = Transcript::<C>::new(&PLONK_CHALLENGES)?;
+    bind_verifying_key(&mut fs, GAMMA, &vk)?;
+    let gamma_prefix = fs.state();
+
+    let size = fr_from_usize(vk.size)?;
+
+    let mut public_input_omegas = Vec::with_capacity(vk.nb_public_variables);
+    let mut accw = Fr::one();
+    for _ in 0..vk.nb_public_variables {
+        public_input_omegas.push(accw);
+        accw *= vk.generator;
+    }
+
+    let mut commitment_omegas = Vec::with_capacity(vk.commitment_constraint_indexes.len());
+    for index in vk.commitment_constraint_indexes.iter() {
+        // Both come from the untrusted key, which may not have been validated
+        let position = vk.nb_public_variables.checked_add(*index).ok_or(
+            VkError::CommitmentIndexOutOfDomain {
+                index: *index,
+                size: vk.size,
+            },
+        )?;
+        let exponent = fr_from_usize(position)?;
+        commitment_omegas.push(vk.generator.pow(exponent));
+    }
+
+    Ok(PreparedPlonkVerifyingKey {
+        vk,
+        gamma_prefix,
+        size,
+        public_input_omegas,
+        commitment_omegas,
+        bsb22_dst: PLONK_COMMITMENT_DST.to_vec(),
+        hash_to_field: PhantomData,
+    })
 }
 
 /// Verifies a PLONK proof
@@ -42,35 +407,87 @@ pub(crate) struct PlonkVerifyingKey {
 ///
 /// # Returns
 ///
-/// * `Result<bool, PlonkError>` - Returns true if the proof is valid, or an error if verification fails
-pub fn verify_plonk(
-    vk: &PlonkVerifyingKey,
+/// * `Result<(), Error>` - Returns `Ok(())` if the proof is valid, or an
+///   [`Error::ProofRejected`] naming the failed check if it is not
+pub fn verify_plonk<C: FiatShamirHash, F: HashToField>(
+    vk: &PreparedPlonkVerifyingKey<C, F>,
     proof: &PlonkProof,
     public_inputs: &[Fr],
-) -> Result<bool, PlonkError> {
-    // Check if the number of BSB22 commitments matches the number of Qcp in the verifying key
-    if proof.bsb22_commitments.len() != vk.qcp.len() {
-        return Err(PlonkError::GeneralError(Error::Bsb22CommitmentMismatch));
+) -> Result<(), Error> {
+    let openings = compute_kzg_openings(vk, proof, public_inputs)?;
+
+    // Perform batch verification
+    kzg::batch_verify_multi_points(
+        &openings.digests,
+        &openings.proofs,
+        &openings.points,
+        &vk.vk.kzg,
+    )
+}
+
+/// Verifies several PLONK proofs against the same verifying key
+///
+/// The transcript and linearization steps are run for each proof, then the KZG openings of
+/// all the proofs are checked together with a single pairing, as in
+/// [`kzg::batch_verify_multi_points`].
+///
+/// # Arguments
+///
+/// * `vk` - The verifying key
+/// * `proofs` - The PLONK proofs, each paired with its public inputs
+///
+/// # Returns
+///
+/// * `Result<(), Error>` - Returns `Ok(())` if all the proofs are valid, or an error if the
+///   verification of any of them fails
+pub fn verify_plonk_batch<C: FiatShamirHash, F: HashToField>(
+    vk: &PreparedPlonkVerifyingKey<C, F>,
+    proofs: &[(PlonkProof, &[Fr])],
+) -> Result<(), Error> {
+    if proofs.is_empty() {
+        return Ok(());
     }
 
-    // Check if the number of public inputs matches the number of public variables in the verifying key
-    if public_inputs.len() != vk.nb_public_variables {
-        return Err(PlonkError::GeneralError(Error::InvalidWitness));
+    let mut digests = Vec::with_capacity(2 * proofs.len());
+    let mut opening_proofs = Vec::with_capacity(2 * proofs.len());
+    let mut points = Vec::with_capacity(2 * proofs.len());
+
+    for (proof, public_inputs) in proofs {
+        let openings = compute_kzg_openings(vk, proof, public_inputs)?;
+        digests.extend_from_slice(&openings.digests);
+        opening_proofs.extend_from_slice(&openings.proofs);
+        points.extend_from_slice(&openings.points);
     }
 
-    // Initialize the Fiat-Shamir transcript
-    let mut fs = Transcript::new(Some(
-        [
-            GAMMA.to_string(),
-            BETA.to_string(),
-            ALPHA.to_string(),
-            ZETA.to_string(),
-        ]
-        .to_vec(),
-    ))?;
+    kzg::batch_verify_multi_points(&digests, &opening_proofs, &points, &vk.vk.kzg)
+}
+
+// The openings a PLONK proof is reduced to: the folded batch opening at ζ and the opening of z
+// at ωζ
+struct KzgOpenings {
+    digests: [kzg::Digest; 2],
+    proofs: [kzg::OpeningProof; 2],
+    points: [Fr; 2],
+}
+
+// Runs every step of the verification but the final pairing check
+fn compute_kzg_openings<C: FiatShamirHash, F: HashToField>(
+    pvk: &PreparedPlonkVerifyingKey<C, F>,
+    proof: &PlonkProof,
+    public_inputs: &[Fr],
+) -> Result<KzgOpenings, Error> {
+    let vk = &pvk.vk;
+
+    // Check the shape of the proof and the public inputs before indexing into them
+    proof.validate_against(vk, public_inputs)?;
+
+    // Initialize the Fiat-Shamir transcript, with the verifying key already bound
+    let mut fs = Transcript::resume(&PLONK_CHALLENGES, pvk.gamma_prefix.clone());
 
-    // Bind public data to the transcript
-    bind_public_data(&mut fs, GAMMA, vk, public_inputs)?;
+    // Bind the public inputs to the transcript
+    for public_input in public_inputs.iter() {
+        fs.bind(GAMMA, &public_input.into_u256().to_bytes_be())?;
+    }
 
     // Derive gamma challenge: γ
     let gamma = derive_randomness(
@@ -96,60 +513,49 @@ pub fn verify_plonk(
 
     // Compute zh_zeta = ζⁿ - 1
     let one = Fr::one();
-    let n = U256::from(vk.size as u64);
-    let n =
-        Fr::from_slice(&n.to_bytes_be()).map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
-    let zeta_power_n = zeta.pow(n);
+    let zeta_power_n = zeta.pow(pvk.size);
     let zh_zeta = zeta_power_n - one;
 
     // Compute Lagrange polynomial at ζ: L₁(ζ) = (ζⁿ - 1) / (n * (ζ - 1))
-    let mut lagrange_one = (zeta - one).inverse().ok_or(Error::InverseNotFound)?;
+    let mut lagrange_one = (zeta - one)
+        .inverse()
+        .ok_or(ProofCheck::DegenerateChallenge)?;
     lagrange_one *= zh_zeta;
     lagrange_one *= vk.size_inv;
 
     // Compute PI = ∑_{i<n} Lᵢ(ζ) * wᵢ
     let mut pi = Fr::zero();
-    let mut accw = Fr::one();
     let mut dens = Vec::with_capacity(public_inputs.len());
 
     // Compute [ζ-1, ζ-ω, ζ-ω², ...]
-    for _ in 0..public_inputs.len() {
+    for accw in pvk.public_input_omegas.iter() {
         let mut temp = zeta;
-        temp -= accw;
+        temp -= *accw;
         dens.push(temp);
-        accw *= vk.generator;
     }
 
     // Compute [1/(ζ-1), 1/(ζ-ω), 1/(ζ-ω²), ...]
     let inv_dens = batch_invert(&dens)?;
 
-    accw = Fr::one();
     let mut xi_li;
     for (i, public_input) in public_inputs.iter().enumerate() {
         // Compute Lᵢ(ζ) * wᵢ = (ζⁿ - 1) / (n * (ζ - ωⁱ)) * wᵢ
         xi_li = zh_zeta;
         xi_li *= inv_dens[i];
         xi_li *= vk.size_inv;
-        xi_li *= accw;
+        xi_li *= pvk.public_input_omegas[i];
         xi_li *= *public_input;
-        accw *= vk.generator;
         pi += xi_li;
     }
 
     // Handle BSB22 commitments
-    let mut hash_to_field = crate::hash_to_field::WrappedHashToField::new(b"BSB22-Plonk")?;
+    let mut hash_to_field = F::new(&pvk.bsb22_dst)?;
 
-    for i in 0..vk.commitment_constraint_indexes.len() {
+    for (i, w_pow_i) in pvk.commitment_omegas.iter().copied().enumerate() {
         hash_to_field.write(&g1_to_bytes(&proof.bsb22_commitments[i])?);
-        let hash_bts = hash_to_field.sum()?;
+        let hashed_cmt = hash_to_field.sum()?;
         hash_to_field.reset();
-        let hashed_cmt = Fr::from_bytes_be_mod_order(&hash_bts)
-            .map_err(|_| Error::FailedToGetFrFromRandomBytes)?;
 
-        let exponent =
-            U256::from((vk.nb_public_variables + vk.commitment_constraint_indexes[i]) as u64);
-        let exponent = Fr::new(exponent).ok_or(Error::BeyondTheModulus)?;
-        let w_pow_i = vk.generator.pow(exponent);
         let mut den = zeta;
         den -= w_pow_i;
         let mut lagrange = zh_zeta;
@@ -210,7 +616,7 @@ pub fn verify_plonk(
     let opening_lin_pol = proof.batched_proof.claimed_values[0];
 
     if const_lin != opening_lin_pol {
-        return Err(Error::OpeningPolyMismatch.into());
+        return Err(ProofCheck::LinearizedPolynomialOpening.into());
     }
 
     // Compute coefficients for the linearized polynomial
@@ -234,9 +640,7 @@ pub fn verify_plonk(
     let rl = l * r;
 
     // Compute powers of zeta
-    let n_plus_two = U256::from(vk.size as u64 + 2);
-    let n_plus_two = Fr::from_slice(&n_plus_two.to_bytes_be())
-        .map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
+    let n_plus_two = pvk.size + one + one;
 
     // -ζⁿ⁺²*(ζⁿ-1)
     let mut zeta_n_plus_two_zh = zeta.pow(n_plus_two);
@@ -294,7 +698,7 @@ pub fn verify_plonk(
     digests_to_fold[5] = vk.s[1];
 
     // Fold the proof
-    let (folded_proof, folded_digest) = kzg::fold_proof(
+    let (folded_proof, folded_digest) = kzg::fold_proof::<C>(
         digests_to_fold,
         &proof.batched_proof,
         &zeta,
@@ -303,25 +707,18 @@ pub fn verify_plonk(
 
     let shifted_zeta = zeta * vk.generator;
 
-    let folded_digest: AffineG1 = folded_digest;
-
-    // Perform batch verification
-    kzg::batch_verify_multi_points(
-        [folded_digest, proof.z].to_vec(),
-        [folded_proof, proof.z_shifted_opening].to_vec(),
-        [zeta, shifted_zeta].to_vec(),
-        &vk.kzg,
-    )?;
-
-    Ok(true)
+    Ok(KzgOpenings {
+        digests: [folded_digest, proof.z],
+        proofs: [folded_proof, proof.z_shifted_opening],
+        points: [zeta, shifted_zeta],
+    })
 }
 
-fn bind_public_data(
-    transcript: &mut Transcript,
+fn bind_verifying_key<C: FiatShamirHash>(
+    transcript: &mut Transcript<C>,
     challenge: &str,
     vk: &PlonkVerifyingKey,
-    public_inputs: &[Fr],
-) -> Result<(), PlonkError> {
+) -> Result<(), Error> {
     transcript.bind(challenge, &g1_to_bytes(&vk.s[0])?)?;
     transcript.bind(challenge, &g1_to_bytes(&vk.s[1])?)?;
     transcript.bind(challenge, &g1_to_bytes(&vk.s[2])?)?;
@@ -336,18 +733,14 @@ fn bind_public_data(
         transcript.bind(challenge, &g1_to_bytes(qcp)?)?;
     }
 
-    for public_input in public_inputs.iter() {
-        transcript.bind(challenge, &public_input.into_u256().to_bytes_be())?;
-    }
-
     Ok(())
 }
 
-fn derive_randomness(
-    transcript: &mut Transcript,
+fn derive_randomness<C: FiatShamirHash>(
+    transcript: &mut Transcript<C>,
     challenge: &str,
     points: Option<Vec<AffineG1>>,
-) -> Result<Fr, PlonkError> {
+) -> Result<Fr, Error> {
     if let Some(points) = points {
         for point in points {
             let buf = g1_to_bytes(&point)?;
@@ -356,12 +749,11 @@ fn derive_randomness(
     }
 
     let b = transcript.compute_challenge(challenge)?;
-    let x = Fr::from_bytes_be_mod_order(b.as_slice())
-        .map_err(|e| PlonkError::GeneralError(Error::Field(e)))?;
+    let x = Fr::from_bytes_be_mod_order(&b).map_err(InternalError::Field)?;
     Ok(x)
 }
 
-fn batch_invert(elements: &[Fr]) -> Result<Vec<Fr>, PlonkError> {
+fn batch_invert(elements: &[Fr]) -> Result<Vec<Fr>, Error> {
     let mut elements = elements.to_vec();
     batch_inversion(&mut elements);
     Ok(elements)
@@ -394,3 +786,259 @@ fn batch_inversion_and_mul(v: &mut [Fr], coeff: &Fr) {
         tmp = new_tmp;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fr, g1, plonk_vk};
+    use bn::{Group, G1};
+
+    type TestKey = PreparedPlonkVerifyingKey<Sha256, WrappedHashToField>;
+
+    // The KZG trapdoor of `plonk_vk`, [τ]₂ being its second G2 point
+    const TAU: u64 = 12345;
+
+    fn prepared_vk() -> TestKey {
+        prepare_plonk_verifying_key(plonk_vk()).expect("valid verifying key")
+    }
+
+    // The quotient [(d(τ) - v) / (τ - x)]₁ opening the digest `digest` to `value` at `point`
+    fn open(digest: AffineG1, value: Fr, point: Fr) -> AffineG1 {
+        let inv = (fr(TAU) - point).inverse().expect("point other than τ");
+        AffineG1::from_jacobian((G1::from(digest) - G1::one() * value) * inv)
+            .expect("non-zero quotient")
+    }
+
+    // Forge a proof passing every check from points and claimed values picked from `seed`: the
+    // opening of the linearized polynomial is computed from the challenges and the openings
+    // with the trapdoor
+    fn forge_proof(pvk: &TestKey, public_inputs: &[Fr], seed: u64) -> PlonkProof {
+        let vk = &pvk.vk;
+        let lro = [g1(fr(seed + 1)), g1(fr(seed + 2)), g1(fr(seed + 3))];
+        let z = g1(fr(seed + 4));
+        let h = [g1(fr(seed + 5)), g1(fr(seed + 6)), g1(fr(seed + 7))];
+        let bsb22_commitments = vec![g1(fr(seed + 8))];
+        // l, r, o, s1, s2 and qc, after the linearized polynomial
+        let mut claimed_values = vec![Fr::zero()];
+        claimed_values.extend((9..15).map(|i| fr(seed + i)));
+
+        let mut fs = Transcript::resume(&PLONK_CHALLENGES, pvk.gamma_prefix.clone());
+        for public_input in public_inputs {
+            fs.bind(GAMMA, &public_input.into_u256().to_bytes_be())
+                .expect("bound public input");
+        }
+        derive_randomness(&mut fs, GAMMA, Some(lro.to_vec())).expect("gamma");
+        derive_randomness(&mut fs, BETA, None).expect("beta");
+        let mut alpha_deps = bsb22_commitments.clone();
+        alpha_deps.push(z);
+        let alpha = derive_randomness(&mut fs, ALPHA, Some(alpha_deps)).expect("alpha");
+        let zeta = derive_randomness(&mut fs, ZETA, Some(h.to_vec())).expect("zeta");
+
+        // Lᵢ(ζ) = ωⁱ * (ζⁿ - 1) / (n * (ζ - ωⁱ))
+        let zh_zeta = zeta.pow(pvk.size) - Fr::one();
+        let lagrange = |omega: Fr| {
+            zh_zeta * omega * (zeta - omega).inverse().expect("ζ outside the domain") * vk.size_inv
+        };
+        let mut hash_to_field =
+            <WrappedHashToField>::new(&pvk.bsb22_dst).expect("valid domain separator");
+        hash_to_field.write(&g1_to_bytes(&bsb22_commitments[0]).expect("affine point"));
+        let pi = pvk
+            .public_input_omegas
+            .iter()
+            .zip(public_inputs)
+            .fold(Fr::zero(), |acc, (omega, input)| {
+                acc + lagrange(*omega) * *input
+            })
+            + lagrange(pvk.commitment_omegas[0]) * hash_to_field.sum().expect("hashed commitment");
+
+        // z(ωζ) = 0 cancels the permutation term of the constant of the linearized polynomial
+        claimed_values[0] = alpha * alpha * lagrange(Fr::one()) - pi;
+        let mut proof = PlonkProof::from_parts(
+            lro,
+            z,
+            h,
+            bsb22_commitments,
+            AffineG1::one(),
+            claimed_values,
+            AffineG1::one(),
+            Fr::zero(),
+        );
+
+        // The quotients are not bound to any challenge of the proof
+        let openings = compute_kzg_openings(pvk, &proof, public_inputs).expect("valid openings");
+        proof.batched_proof.h = open(
+            openings.digests[0],
+            openings.proofs[0].claimed_value,
+            openings.points[0],
+        );
+        proof.z_shifted_opening.h = open(z, Fr::zero(), openings.points[1]);
+        proof
+    }
+
+    fn public_inputs() -> [Fr; 2] {
+        [fr(3), fr(4)]
+    }
+
+    #[test]
+    fn test_verify_plonk() {
+        let pvk = prepared_vk();
+        let proof = forge_proof(&pvk, &public_inputs(), 1000);
+        assert!(verify_plonk(&pvk, &proof, &public_inputs()).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.z_shifted_opening.h = g1(fr(7));
+        assert!(matches!(
+            verify_plonk(&pvk, &tampered, &public_inputs()),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+        assert!(matches!(
+            verify_plonk(&pvk, &proof, &[fr(3), fr(5)]),
+            Err(Error::ProofRejected(
+                ProofCheck::LinearizedPolynomialOpening
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_plonk_verifier_rejects_tampered_proof() {
+        let pvk = prepared_vk();
+        let vk = plonk_vk().to_gnark_bytes().unwrap();
+        let mut proof = forge_proof(&pvk, &public_inputs(), 1000);
+        assert!(crate::PlonkVerifier::verify(
+            &proof.to_gnark_bytes().unwrap(),
+            &vk,
+            &public_inputs()
+        )
+        .is_ok());
+
+        proof.batched_proof.h = g1(fr(7));
+        assert!(matches!(
+            crate::PlonkVerifier::verify(&proof.to_gnark_bytes().unwrap(), &vk, &public_inputs()),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+
+    #[test]
+    fn test_verify_plonk_batch() {
+        let pvk = prepared_vk();
+        let inputs = [public_inputs(), [fr(5), fr(6)], [fr(7), fr(8)]];
+        let mut proofs: Vec<(PlonkProof, &[Fr])> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, inputs)| {
+                (
+                    forge_proof(&pvk, inputs, 1000 * (i as u64 + 1)),
+                    inputs.as_slice(),
+                )
+            })
+            .collect();
+        assert!(verify_plonk_batch(&pvk, &proofs).is_ok());
+
+        proofs[1].0.batched_proof.h = g1(fr(7));
+        assert!(matches!(
+            verify_plonk_batch(&pvk, &proofs),
+            Err(Error::ProofRejected(ProofCheck::Pairing))
+        ));
+    }
+
+    #[test]
+    fn test_verify_plonk_batch_rejects_invalid_public_inputs() {
+        let pvk = prepared_vk();
+        let inputs = public_inputs();
+        let proof = forge_proof(&pvk, &inputs, 1000);
+        assert!(matches!(
+            verify_plonk_batch(&pvk, &[(proof.clone(), &inputs), (proof, &inputs[..1])]),
+            Err(Error::ProofRejected(
+                ProofCheck::NumberOfPublicInputs { .. }
+            ))
+        ));
+    }
+
+    fn validate_with(edit: impl FnOnce(&mut PlonkVerifyingKey)) -> Result<(), Error> {
+        let mut vk = plonk_vk();
+        edit(&mut vk);
+        vk.validate()
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(plonk_vk().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_domain() {
+        assert!(matches!(
+            validate_with(|vk| vk.size = 6),
+            Err(Error::InvalidVerifyingKey(VkError::InvalidDomainSize(6)))
+        ));
+        assert!(matches!(
+            validate_with(|vk| vk.size_inv = fr(4).inverse().unwrap()),
+            Err(Error::InvalidVerifyingKey(
+                VkError::InvalidDomainSizeInverse
+            ))
+        ));
+        // ω² has order 4 and 2 is not a root of unity of order 8
+        assert!(matches!(
+            validate_with(|vk| vk.generator = vk.generator * vk.generator),
+            Err(Error::InvalidVerifyingKey(VkError::InvalidDomainGenerator))
+        ));
+        assert!(matches!(
+            validate_with(|vk| vk.generator = fr(2)),
+            Err(Error::InvalidVerifyingKey(VkError::InvalidDomainGenerator))
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_coset_shift_in_domain() {
+        for shift in [Fr::one(), -Fr::one(), plonk_vk().generator] {
+            assert!(matches!(
+                validate_with(|vk| vk.coset_shift = shift),
+                Err(Error::InvalidVerifyingKey(VkError::InvalidCosetShift))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_commitment_indexes() {
+        // The last constraint of the domain is at 2 + 5
+        assert!(validate_with(|vk| vk.commitment_constraint_indexes = vec![5]).is_ok());
+        for index in [6, usize::MAX] {
+            assert!(matches!(
+                validate_with(|vk| vk.commitment_constraint_indexes = vec![index]),
+                Err(Error::InvalidVerifyingKey(VkError::CommitmentIndexOutOfDomain {
+                    index: i,
+                    size: 8,
+                })) if i == index
+            ));
+        }
+        assert!(matches!(
+            validate_with(|vk| vk.commitment_constraint_indexes = vec![3, 4]),
+            Err(Error::InvalidVerifyingKey(
+                VkError::InvalidNumberOfCommitmentIndexes {
+                    expected: 1,
+                    got: 2,
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_prepare_rejects_overflowing_commitment_index() {
+        let mut vk = plonk_vk();
+        vk.commitment_constraint_indexes = vec![usize::MAX];
+        assert!(matches!(
+            prepare_plonk_verifying_key::<Sha256, WrappedHashToField>(vk),
+            Err(Error::InvalidVerifyingKey(
+                VkError::CommitmentIndexOutOfDomain {
+                    index: usize::MAX,
+                    size: 8,
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_verify_plonk_empty_batch() {
+        assert!(verify_plonk_batch(&prepared_vk(), &[]).is_ok());
+    }
+}
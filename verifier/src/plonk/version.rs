@@ -0,0 +1,70 @@
+use crate::error::{ArgumentError, Error};
+
+/// A gnark release, which fixes how Plonk verifying keys are serialized.
+///
+/// The proof layout is the same in every supported release. Releases before v0.9 are not
+/// supported: their `VerifyingKey.WriteTo`, in gnark's `backend/plonk/bn254/marshal.go`, does
+/// not write the KZG SRS the key refers to, which had to be loaded separately, so such keys
+/// cannot be verified against on their own. They are reported as
+/// [`ArgumentError::UnsupportedFormatVersion`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GnarkVersion {
+    /// gnark v0.9: the KZG verifying key is [G₁, G₂, [α]G₂], without pairing lines.
+    V0_9,
+    /// gnark v0.10: the KZG verifying key also holds the precomputed pairing lines of G₂ and
+    /// [α]G₂.
+    V0_10,
+    /// gnark v0.11, which serializes keys like v0.10.
+    #[default]
+    V0_11,
+}
+
+impl GnarkVersion {
+    /// Parses a release version such as `v0.10.0` or `0.11`, with or without the leading `v`
+    /// and the patch number.
+    pub fn parse(version: &str) -> Result<Self, Error> {
+        let version = version.strip_prefix('v').unwrap_or(version);
+        let mut parts = version.split('.');
+
+        match (parts.next(), parts.next()) {
+            (Some("0"), Some("9")) => Ok(Self::V0_9),
+            (Some("0"), Some("10")) => Ok(Self::V0_10),
+            (Some("0"), Some("11")) => Ok(Self::V0_11),
+            _ => Err(ArgumentError::UnsupportedFormatVersion.into()),
+        }
+    }
+
+    /// Whether the KZG verifying key holds the precomputed pairing lines.
+    pub(crate) fn has_kzg_lines(self) -> bool {
+        match self {
+            Self::V0_9 => false,
+            Self::V0_10 | Self::V0_11 => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for version in ["v0.9", "0.9.1", "v0.9.0"] {
+            assert_eq!(GnarkVersion::parse(version).unwrap(), GnarkVersion::V0_9);
+        }
+        for version in ["v0.10", "0.10.0", "v0.10.1"] {
+            assert_eq!(GnarkVersion::parse(version).unwrap(), GnarkVersion::V0_10);
+        }
+        for version in ["v0.11", "v0.11.1", "0.11"] {
+            assert_eq!(GnarkVersion::parse(version).unwrap(), GnarkVersion::V0_11);
+        }
+        for version in ["v0.8.0", "v0.12", "v1.0", "0", "", "v0.1O"] {
+            assert!(matches!(
+                GnarkVersion::parse(version),
+                Err(Error::InvalidArgument(
+                    ArgumentError::UnsupportedFormatVersion
+                ))
+            ));
+        }
+    }
+}
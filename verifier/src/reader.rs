@@ -0,0 +1,192 @@
+use bn::{AffineG1, AffineG2, Fq, Fq2, Fr};
+
+use crate::{
+    converter::{
+        bytes_to_g1_point, bytes_to_g2_point, unchecked_bytes_to_g1_point,
+        unchecked_bytes_to_g2_point, PointEncoding,
+    },
+    error::{DecodeError, Error},
+    pairing::LineEvaluationAff,
+};
+
+/// Whether a loader must consume its whole input buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ParseMode {
+    /// Bytes after the end of the serialized object are ignored.
+    #[default]
+    Lenient,
+    /// Any byte left after the end of the serialized object is an error.
+    Strict,
+}
+
+/// How much a verifying key is checked when it is loaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VkValidation {
+    /// The key comes from a trusted source, its points are only decoded. Compressed points are
+    /// on the curve by construction, but G2 points are not checked to be in the subgroup and
+    /// uncompressed points are not checked to be on the curve. The KZG pairing lines of a
+    /// Plonk key are used as serialized.
+    #[default]
+    Trusted,
+    /// Every point is checked to be on the curve, and every G2 point to be in the subgroup of
+    /// order r, the KZG pairing lines of a Plonk key are precomputed again and compared with
+    /// the serialized ones, then the key itself is checked with
+    /// [`Groth16VerifyingKey::validate`](crate::Groth16VerifyingKey::validate) or
+    /// [`PlonkVerifyingKey::validate`](crate::PlonkVerifyingKey::validate), as is needed for
+    /// keys from untrusted sources.
+    Full,
+}
+
+/// A bounds-checked cursor over a gnark serialized object.
+///
+/// Every read either returns the requested data or an error, it never panics on short or
+/// malformed input.
+#[derive(Clone, Debug)]
+pub(crate) struct ByteReader<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(buffer: &'a [u8]) -> Self {
+        Self { buffer, offset: 0 }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.buffer.len() - self.offset
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Reads `len` bytes, naming `field` in the error if the input is too short.
+    pub(crate) fn read_bytes(
+        &mut self,
+        len: usize,
+        field: &'static str,
+    ) -> Result<&'a [u8], Error> {
+        if len > self.remaining() {
+            return Err(Error::malformed(
+                self.offset,
+                field,
+                DecodeError::UnexpectedEndOfInput,
+            ));
+        }
+
+        let bytes = &self.buffer[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_u32(&mut self, field: &'static str) -> Result<u32, Error> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.read_bytes(4, field)?);
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    pub(crate) fn read_u64(&mut self, field: &'static str) -> Result<u64, Error> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.read_bytes(8, field)?);
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Reads a u64 into a narrower integer, such as a `usize` on 32-bit targets.
+    pub(crate) fn read_u64_as<T: TryFrom<u64>>(&mut self, field: &'static str) -> Result<T, Error> {
+        let offset = self.offset;
+        T::try_from(self.read_u64(field)?)
+            .map_err(|_| Error::malformed(offset, field, DecodeError::IntegerTooLarge))
+    }
+
+    /// Reads a u32 length prefix and checks that the buffer still holds that many items of
+    /// `item_size` bytes.
+    pub(crate) fn read_len_u32(
+        &mut self,
+        item_size: usize,
+        field: &'static str,
+    ) -> Result<usize, Error> {
+        let offset = self.offset;
+        let len = self.read_u32(field)? as usize;
+        match len.checked_mul(item_size) {
+            Some(size) if size <= self.remaining() => Ok(len),
+            _ => Err(Error::malformed(
+                offset,
+                field,
+                DecodeError::InvalidLengthPrefix,
+            )),
+        }
+    }
+
+    pub(crate) fn read_fr(&mut self, field: &'static str) -> Result<Fr, Error> {
+        let offset = self.offset;
+        Fr::from_slice(self.read_bytes(32, field)?)
+            .map_err(|_| Error::malformed(offset, field, DecodeError::NonCanonicalCoordinate))
+    }
+
+    pub(crate) fn read_fq(&mut self, field: &'static str) -> Result<Fq, Error> {
+        let offset = self.offset;
+        Fq::from_slice(self.read_bytes(32, field)?)
+            .map_err(|_| Error::malformed(offset, field, DecodeError::NonCanonicalCoordinate))
+    }
+
+    /// Reads a precomputed pairing line as R0, R1, each E2 being written as A0, A1.
+    pub(crate) fn read_line(&mut self, field: &'static str) -> Result<LineEvaluationAff, Error> {
+        let r0 = Fq2::new(self.read_fq(field)?, self.read_fq(field)?);
+        let r1 = Fq2::new(self.read_fq(field)?, self.read_fq(field)?);
+
+        Ok(LineEvaluationAff { r0, r1 })
+    }
+
+    pub(crate) fn read_g1(
+        &mut self,
+        encoding: PointEncoding,
+        field: &'static str,
+    ) -> Result<AffineG1, Error> {
+        let offset = self.offset;
+        bytes_to_g1_point(self.read_bytes(encoding.g1_size(), field)?, encoding)
+            .map_err(|kind| Error::malformed(offset, field, kind))
+    }
+
+    pub(crate) fn read_g2(
+        &mut self,
+        encoding: PointEncoding,
+        field: &'static str,
+    ) -> Result<AffineG2, Error> {
+        let offset = self.offset;
+        bytes_to_g2_point(self.read_bytes(encoding.g2_size(), field)?, encoding)
+            .map_err(|kind| Error::malformed(offset, field, kind))
+    }
+
+    pub(crate) fn read_unchecked_g1(
+        &mut self,
+        encoding: PointEncoding,
+        field: &'static str,
+    ) -> Result<AffineG1, Error> {
+        let offset = self.offset;
+        unchecked_bytes_to_g1_point(self.read_bytes(encoding.g1_size(), field)?, encoding)
+            .map_err(|kind| Error::malformed(offset, field, kind))
+    }
+
+    pub(crate) fn read_unchecked_g2(
+        &mut self,
+        encoding: PointEncoding,
+        field: &'static str,
+    ) -> Result<AffineG2, Error> {
+        let offset = self.offset;
+        unchecked_bytes_to_g2_point(self.read_bytes(encoding.g2_size(), field)?, encoding)
+            .map_err(|kind| Error::malformed(offset, field, kind))
+    }
+
+    /// Ends the parsing, rejecting leftover bytes in [`ParseMode::Strict`].
+    pub(crate) fn finish(self, mode: ParseMode) -> Result<(), Error> {
+        if mode == ParseMode::Strict && !self.is_empty() {
+            return Err(Error::malformed(
+                self.offset,
+                "end of input",
+                DecodeError::TrailingBytes,
+            ));
+        }
+
+        Ok(())
+    }
+}
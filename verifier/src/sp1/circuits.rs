@@ -0,0 +1,216 @@
+use sha2::{Digest, Sha256};
+
+use super::Sp1Error;
+
+/// The proof system of an SP1 circuit verifying key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sp1ProofSystem {
+    /// The Groth16 circuit.
+    Groth16,
+    /// The Plonk circuit.
+    Plonk,
+}
+
+// A verifying key embedded in the crate, with the hex encoded SHA-256 digest it is pinned to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct EmbeddedVk {
+    bytes: &'static [u8],
+    sha256: &'static str,
+}
+
+impl EmbeddedVk {
+    // The first 4 bytes of the SHA-256 digest of a vk are the selector SP1 prepends to proofs.
+    fn has_selector(&self, selector: &[u8]) -> bool {
+        selector.len() == 4
+            && self.sha256.len() >= 8
+            && self.sha256[..8].eq_ignore_ascii_case(&hex::encode(selector))
+    }
+
+    fn bytes(&self) -> Result<&'static [u8], Sp1Error> {
+        let pinned = self
+            .sha256
+            .get(..64)
+            .and_then(|digest| hex::decode(digest).ok())
+            .ok_or(Sp1Error::VkDigestMismatch)?;
+
+        if Sha256::digest(self.bytes).as_slice() != pinned.as_slice() {
+            return Err(Sp1Error::VkDigestMismatch);
+        }
+
+        Ok(self.bytes)
+    }
+}
+
+/// The Groth16 and Plonk verifying keys of an SP1 circuit release, embedded in the crate.
+///
+/// Each release is behind its own cargo feature: `sp1-v2`, `sp1-v3`, `sp1-v4` and `sp1-v5`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sp1Circuit {
+    version: &'static str,
+    groth16_vk: EmbeddedVk,
+    plonk_vk: EmbeddedVk,
+    nb_public_inputs: usize,
+}
+
+// The vks are read from `vks/<version>/`, next to the `.sha256` files that pin them.
+#[allow(unused_macros)]
+macro_rules! sp1_circuit {
+    ($version:literal, $nb_public_inputs:expr) => {
+        Sp1Circuit {
+            version: $version,
+            groth16_vk: EmbeddedVk {
+                bytes: include_bytes!(concat!("../../vks/", $version, "/groth16_vk.bin")),
+                sha256: include_str!(concat!("../../vks/", $version, "/groth16_vk.bin.sha256")),
+            },
+            plonk_vk: EmbeddedVk {
+                bytes: include_bytes!(concat!("../../vks/", $version, "/plonk_vk.bin")),
+                sha256: include_str!(concat!("../../vks/", $version, "/plonk_vk.bin.sha256")),
+            },
+            nb_public_inputs: $nb_public_inputs,
+        }
+    };
+}
+
+// Up to v4 the circuits expose the program vkey hash and the committed values digest. From
+// v5 on they also expose the exit code, the vk root and the proof nonce.
+const CIRCUITS: &[Sp1Circuit] = &[
+    #[cfg(feature = "sp1-v2")]
+    sp1_circuit!("v2.0.0", 2),
+    #[cfg(feature = "sp1-v3")]
+    sp1_circuit!("v3.0.0", 2),
+    #[cfg(feature = "sp1-v4")]
+    sp1_circuit!("v4.0.0-rc.3", 2),
+    #[cfg(feature = "sp1-v5")]
+    sp1_circuit!("v5.0.0", 5),
+];
+
+impl Sp1Circuit {
+    /// Returns the circuit releases embedded in the crate.
+    pub fn all() -> &'static [Sp1Circuit] {
+        CIRCUITS
+    }
+
+    /// Looks up an embedded circuit release by version, with or without the leading `v`.
+    pub fn by_version(version: &str) -> Option<&'static Sp1Circuit> {
+        let version = version.strip_prefix('v').unwrap_or(version);
+
+        CIRCUITS
+            .iter()
+            .find(|circuit| &circuit.version[1..] == version)
+    }
+
+    /// Looks up an embedded verifying key by the 4-byte selector SP1 prepends to its proofs.
+    ///
+    /// # Returns
+    ///
+    /// The circuit release the verifying key belongs to and its proof system, or `None` if
+    /// no embedded verifying key has this selector.
+    pub fn by_selector(selector: &[u8]) -> Option<(&'static Sp1Circuit, Sp1ProofSystem)> {
+        CIRCUITS.iter().find_map(|circuit| {
+            if circuit.groth16_vk.has_selector(selector) {
+                Some((circuit, Sp1ProofSystem::Groth16))
+            } else if circuit.plonk_vk.has_selector(selector) {
+                Some((circuit, Sp1ProofSystem::Plonk))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns the version of the circuit release, such as `v2.0.0`.
+    pub fn version(&self) -> &'static str {
+        self.version
+    }
+
+    /// Returns the number of public inputs of the circuits.
+    pub fn nb_public_inputs(&self) -> usize {
+        self.nb_public_inputs
+    }
+
+    /// Returns the Groth16 verifying key, after checking it against its pinned SHA-256 digest.
+    pub fn groth16_vk(&self) -> Result<&'static [u8], Sp1Error> {
+        self.groth16_vk.bytes()
+    }
+
+    /// Returns the Plonk verifying key, after checking it against its pinned SHA-256 digest.
+    pub fn plonk_vk(&self) -> Result<&'static [u8], Sp1Error> {
+        self.plonk_vk.bytes()
+    }
+
+    /// Returns the verifying key of the given proof system.
+    pub fn vk(&self, proof_system: Sp1ProofSystem) -> Result<&'static [u8], Sp1Error> {
+        match proof_system {
+            Sp1ProofSystem::Groth16 => self.groth16_vk(),
+            Sp1ProofSystem::Plonk => self.plonk_vk(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        groth16::load_groth16_verifying_key_from_bytes, plonk::load_plonk_verifying_key_from_bytes,
+        reader::ParseMode, VkValidation,
+    };
+
+    // Runs with the `sp1-v*` features enabled, one pass per embedded vk file
+    #[test]
+    fn test_embedded_vks_match_their_pinned_digest() {
+        for circuit in Sp1Circuit::all() {
+            assert_eq!(Sp1Circuit::by_version(circuit.version), Some(circuit));
+
+            for (vk, proof_system) in [
+                (&circuit.groth16_vk, Sp1ProofSystem::Groth16),
+                (&circuit.plonk_vk, Sp1ProofSystem::Plonk),
+            ] {
+                let digest = Sha256::digest(vk.bytes);
+                assert_eq!(Some(hex::encode(digest).as_str()), vk.sha256.get(..64));
+                assert!(vk.bytes().is_ok());
+                assert_eq!(
+                    Sp1Circuit::by_selector(&digest[..4]),
+                    Some((circuit, proof_system))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_embedded_vks_load() {
+        for circuit in Sp1Circuit::all() {
+            let groth16_vk = circuit.groth16_vk().unwrap();
+            assert!(load_groth16_verifying_key_from_bytes(
+                groth16_vk,
+                ParseMode::Strict,
+                VkValidation::Full
+            )
+            .is_ok());
+            let plonk_vk = circuit.plonk_vk().unwrap();
+            assert!(load_plonk_verifying_key_from_bytes(
+                plonk_vk,
+                ParseMode::Strict,
+                VkValidation::Full
+            )
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_tampered_vk_is_rejected() {
+        // As written by `sha256sum`
+        let vk = EmbeddedVk {
+            bytes: b"verifying key",
+            sha256: "be5e9ebac06a44520023e0c079f7e1cd22248a53dbb83e9994bca35a0177dd34  vk.bin\n",
+        };
+        assert!(vk.bytes().is_ok());
+        assert!(vk.has_selector(&[0xbe, 0x5e, 0x9e, 0xba]));
+        assert!(!vk.has_selector(&[0xbe, 0x5e, 0x9e]));
+        assert!(!vk.has_selector(&[0; 4]));
+
+        let tampered = EmbeddedVk {
+            bytes: b"verifying kez",
+            ..vk
+        };
+        assert!(matches!(tampered.bytes(), Err(Sp1Error::VkDigestMismatch)));
+    }
+}
@@ -0,0 +1,33 @@
+use thiserror_no_std::Error;
+
+use crate::error::Error;
+
+/// Errors returned by the SP1 verifiers.
+#[derive(Debug, Error)]
+pub enum Sp1Error {
+    /// The program vkey hash is not a hex encoded scalar field element.
+    #[error("Invalid program vkey hash")]
+    InvalidProgramVkeyHash,
+    /// The proof does not verify for the program vkey hash and the committed values digest
+    /// of the public values.
+    #[error("Proof does not match the program vkey hash or the committed values digest")]
+    PublicInputsMismatch,
+    /// The selector of the proof does not match any embedded verifying key.
+    #[error("Unknown verifying key selector")]
+    UnknownVkSelector,
+    /// An embedded verifying key does not match its pinned SHA-256 digest.
+    #[error("Verifying key does not match its pinned digest")]
+    VkDigestMismatch,
+    /// The number of public inputs does not match the circuit release.
+    #[error("Invalid number of public inputs: expected {expected}, got {got}")]
+    InvalidNumberOfPublicInputs {
+        /// The number of public inputs of the circuit.
+        expected: usize,
+        /// The number of public inputs given.
+        got: usize,
+    },
+    /// The proof or verifying key could not be processed, or the proof was rejected by a
+    /// check that does not depend on the public inputs.
+    #[error("Verifier error: {0}")]
+    Verifier(#[from] Error),
+}
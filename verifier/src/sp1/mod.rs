@@ -0,0 +1,84 @@
+use alloc::vec::Vec;
+use bn::Fr;
+use sha2::{Digest, Sha256};
+
+mod circuits;
+mod error;
+
+pub use circuits::{Sp1Circuit, Sp1ProofSystem};
+pub use error::Sp1Error;
+
+/// The hash function SP1 applies to the public values to get the committed values digest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PublicValuesHash {
+    /// SHA-256, the default.
+    #[default]
+    Sha256,
+    /// BLAKE3, for programs that commit their public values with it.
+    Blake3,
+}
+
+impl PublicValuesHash {
+    /// Computes the committed values digest of the public values: their hash with the three
+    /// most significant bits cleared, so that it fits in the BN254 scalar field.
+    pub fn committed_values_digest(self, public_values: &[u8]) -> Fr {
+        let mut digest: [u8; 32] = match self {
+            PublicValuesHash::Sha256 => Sha256::digest(public_values).into(),
+            PublicValuesHash::Blake3 => blake3::hash(public_values).into(),
+        };
+        digest[0] &= 0b0001_1111;
+
+        // A 253-bit value is always below the modulus
+        Fr::from_slice(&digest).expect("masked digest is a valid scalar")
+    }
+}
+
+// The program vkey hash is given as the hex encoding of a big-endian scalar, as printed by
+// `vk.bytes32()`.
+pub(crate) fn decode_program_vkey_hash(program_vkey_hash: &str) -> Result<Fr, Sp1Error> {
+    let program_vkey_hash = program_vkey_hash
+        .strip_prefix("0x")
+        .unwrap_or(program_vkey_hash);
+
+    let bytes = hex::decode(program_vkey_hash).map_err(|_| Sp1Error::InvalidProgramVkeyHash)?;
+    if bytes.len() != 32 {
+        return Err(Sp1Error::InvalidProgramVkeyHash);
+    }
+
+    Fr::from_slice(&bytes).map_err(|_| Sp1Error::InvalidProgramVkeyHash)
+}
+
+// SP1 circuits expose the program vkey hash and the committed values digest, followed by the
+// extra public inputs of the newer releases.
+pub(crate) fn sp1_public_inputs(
+    public_values: &[u8],
+    program_vkey_hash: &str,
+    hash: PublicValuesHash,
+    extra_public_inputs: &[Fr],
+) -> Result<Vec<Fr>, Sp1Error> {
+    let mut public_inputs = Vec::with_capacity(2 + extra_public_inputs.len());
+    public_inputs.push(decode_program_vkey_hash(program_vkey_hash)?);
+    public_inputs.push(hash.committed_values_digest(public_values));
+    public_inputs.extend_from_slice(extra_public_inputs);
+
+    Ok(public_inputs)
+}
+
+// Splits a proof into its 4-byte vk selector and the raw gnark proof, and returns the embedded
+// circuit release and vk the selector points to.
+pub(crate) fn embedded_vk(
+    proof: &[u8],
+    proof_system: Sp1ProofSystem,
+) -> Result<(&'static Sp1Circuit, &'static [u8], &[u8]), Sp1Error> {
+    if proof.len() < 4 {
+        return Err(Sp1Error::UnknownVkSelector);
+    }
+    let (selector, raw_proof) = proof.split_at(4);
+
+    match Sp1Circuit::by_selector(selector) {
+        Some((circuit, system)) if system == proof_system => {
+            Ok((circuit, circuit.vk(proof_system)?, raw_proof))
+        }
+        _ => Err(Sp1Error::UnknownVkSelector),
+    }
+}
@@ -0,0 +1,234 @@
+//! Synthetic keys and proofs shared by the unit tests, built from known discrete logarithms.
+
+use alloc::{string::ToString, vec, vec::Vec};
+use bn::{AffineG1, AffineG2, Fq, Fq2, Fr, Group, G1, G2};
+
+use crate::{
+    constants::{GROTH16_COMMITMENT_CHALLENGE_DST, GROTH16_COMMITMENT_DST},
+    converter::g1_to_bytes,
+    hash_to_field::{hash_to_field, HashToField, WrappedHashToField},
+    Groth16Proof, Groth16VerifyingKey, PedersenVerifyingKey, PlonkVerifyingKey,
+};
+
+pub(crate) fn fr(n: u64) -> Fr {
+    Fr::from_str(&n.to_string()).expect("valid scalar")
+}
+
+pub(crate) fn g1(scalar: Fr) -> AffineG1 {
+    AffineG1::from_jacobian(G1::one() * scalar).unwrap_or_default()
+}
+
+pub(crate) fn g2(scalar: Fr) -> AffineG2 {
+    AffineG2::from_jacobian(G2::one() * scalar).expect("non-zero scalar")
+}
+
+pub(crate) fn off_curve_g1() -> AffineG1 {
+    let generator = AffineG1::one();
+    AffineG1::new_unchecked(generator.x(), generator.y() + Fq::one())
+}
+
+pub(crate) fn off_curve_g2() -> AffineG2 {
+    let generator = AffineG2::one();
+    AffineG2::new_unchecked(generator.x(), generator.y() + Fq2::one())
+}
+
+// A point of the twist outside the subgroup of order r, which is almost every point of the twist
+pub(crate) fn non_subgroup_g2() -> AffineG2 {
+    (1..)
+        .find_map(|i| {
+            let x = Fq2::new(Fq::from_str(&i.to_string())?, Fq::one());
+            let y = (x * x * x + G2::b()).sqrt()?;
+            AffineG2::new(x, y)
+                .is_err()
+                .then(|| AffineG2::new_unchecked(x, y))
+        })
+        .expect("a point outside the subgroup")
+}
+
+// α = 2, β = 3, γ = 5, δ = 7 and IC = [11, 13, 17]
+const GROTH16_ALPHA: u64 = 2;
+const GROTH16_BETA: u64 = 3;
+const GROTH16_GAMMA: u64 = 5;
+const GROTH16_DELTA: u64 = 7;
+const GROTH16_IC: [u64; 3] = [11, 13, 17];
+
+/// A Groth16 verifying key with two public inputs and no commitment.
+pub(crate) fn groth16_vk() -> Groth16VerifyingKey {
+    Groth16VerifyingKey::from_parts(
+        g1(fr(GROTH16_ALPHA)),
+        g1(fr(GROTH16_BETA)),
+        g2(fr(GROTH16_BETA)),
+        g2(fr(GROTH16_GAMMA)),
+        g1(fr(GROTH16_DELTA)),
+        g2(fr(GROTH16_DELTA)),
+        GROTH16_IC.iter().map(|k| g1(fr(*k))).collect(),
+        vec![],
+        vec![],
+    )
+}
+
+// The Pedersen key G = [g]₂ and σ = 1/t, so Gσ⁻¹ = -[g * t]₂ and [t]C is a valid proof of
+// knowledge of C
+const GROTH16_PEDERSEN_G: u64 = 19;
+const GROTH16_PEDERSEN_T: u64 = 23;
+// The IC points of the commitment hashes
+const GROTH16_COMMITMENT_IC: [u64; 2] = [29, 31];
+
+/// A Groth16 verifying key with two public inputs and one BSB22 commitment to the first.
+pub(crate) fn groth16_commitment_vk() -> Groth16VerifyingKey {
+    groth16_committed_vk(vec![vec![1]])
+}
+
+/// A Groth16 verifying key with two public inputs and two BSB22 commitments, the first to the
+/// first public input and the second to the hash of the first commitment.
+pub(crate) fn groth16_nested_commitment_vk() -> Groth16VerifyingKey {
+    groth16_committed_vk(vec![vec![1], vec![3]])
+}
+
+fn groth16_committed_vk(committed: Vec<Vec<u32>>) -> Groth16VerifyingKey {
+    let g = fr(GROTH16_PEDERSEN_G);
+    Groth16VerifyingKey::from_parts(
+        g1(fr(GROTH16_ALPHA)),
+        g1(fr(GROTH16_BETA)),
+        g2(fr(GROTH16_BETA)),
+        g2(fr(GROTH16_GAMMA)),
+        g1(fr(GROTH16_DELTA)),
+        g2(fr(GROTH16_DELTA)),
+        GROTH16_IC
+            .iter()
+            .chain(GROTH16_COMMITMENT_IC[..committed.len()].iter())
+            .map(|k| g1(fr(*k)))
+            .collect(),
+        committed
+            .iter()
+            .map(|_| PedersenVerifyingKey::new(g2(g), -g2(g * fr(GROTH16_PEDERSEN_T))))
+            .collect(),
+        committed,
+    )
+}
+
+// Build [C]₁ such that e(A, B) * e(PI, γ) * e(C, -δ) == e(α, -β), the negated β of the
+// verifying key
+fn groth16_krs(pi: Fr, a: Fr, b: Fr) -> AffineG1 {
+    let c = (a * b + fr(GROTH16_ALPHA) * fr(GROTH16_BETA) + pi * fr(GROTH16_GAMMA))
+        * fr(GROTH16_DELTA).inverse().expect("non-zero δ");
+    g1(c)
+}
+
+fn groth16_pi(public_inputs: &[Fr]) -> Fr {
+    public_inputs
+        .iter()
+        .zip(GROTH16_IC[1..].iter())
+        .fold(fr(GROTH16_IC[0]), |acc, (input, k)| acc + *input * fr(*k))
+}
+
+/// A valid proof for [`groth16_vk`] and the public inputs, with [A]₁ = [a]₁ and [B]₂ = [b]₂.
+pub(crate) fn groth16_proof(public_inputs: &[Fr], a: Fr, b: Fr) -> Groth16Proof {
+    let krs = groth16_krs(groth16_pi(public_inputs), a, b);
+    Groth16Proof::from_parts(g1(a), g2(b), krs, vec![], AffineG1::default())
+}
+
+/// A valid proof for [`groth16_commitment_vk`] and the public inputs, committing to the first
+/// public input with the commitment [commitment]₁.
+pub(crate) fn groth16_commitment_proof(
+    public_inputs: &[Fr],
+    a: Fr,
+    b: Fr,
+    commitment: Fr,
+) -> Groth16Proof {
+    groth16_committed_proof(&groth16_commitment_vk(), public_inputs, a, b, &[commitment])
+}
+
+/// A valid proof for [`groth16_nested_commitment_vk`] and the public inputs, with the
+/// commitments [commitments[i]]₁.
+pub(crate) fn groth16_nested_commitment_proof(
+    public_inputs: &[Fr],
+    a: Fr,
+    b: Fr,
+    commitments: [Fr; 2],
+) -> Groth16Proof {
+    groth16_committed_proof(
+        &groth16_nested_commitment_vk(),
+        public_inputs,
+        a,
+        b,
+        &commitments,
+    )
+}
+
+// Hash each commitment with the values it commits to, the public inputs followed by the hashes
+// of the earlier commitments as in gnark, and prove knowledge of all of them at once with the
+// challenge ρ: [t * Σ ρⁱ cᵢ]₁
+fn groth16_committed_proof(
+    vk: &Groth16VerifyingKey,
+    public_inputs: &[Fr],
+    a: Fr,
+    b: Fr,
+    commitments: &[Fr],
+) -> Groth16Proof {
+    let mut witness = public_inputs.to_vec();
+    let mut hashes_serialized = vec![];
+    let mut pi = groth16_pi(public_inputs);
+    for ((commitment, committed), k) in commitments
+        .iter()
+        .zip(vk.public_and_commitment_committed.iter())
+        .zip(GROTH16_COMMITMENT_IC.iter())
+    {
+        let mut hash_to_field =
+            <WrappedHashToField>::new(GROTH16_COMMITMENT_DST).expect("valid domain separator");
+        hash_to_field.write(&g1_to_bytes(&g1(*commitment)).expect("affine point"));
+        for index in committed {
+            hash_to_field.write(&witness[*index as usize - 1].into_u256().to_bytes_be());
+        }
+        let hash: Fr = hash_to_field.sum().expect("hash to field");
+        hashes_serialized.extend_from_slice(&hash.into_u256().to_bytes_be());
+        witness.push(hash);
+        pi = pi + hash * fr(*k) + *commitment;
+    }
+
+    let challenge = hash_to_field(&hashes_serialized, GROTH16_COMMITMENT_CHALLENGE_DST, 1)
+        .expect("hash to field")[0];
+    let mut folded = Fr::zero();
+    let mut r = Fr::one();
+    for commitment in commitments {
+        folded += *commitment * r;
+        r *= challenge;
+    }
+
+    let krs = groth16_krs(pi, a, b);
+    let pok = g1(folded * fr(GROTH16_PEDERSEN_T));
+    Groth16Proof::from_parts(
+        g1(a),
+        g2(b),
+        krs,
+        commitments.iter().map(|c| g1(*c)).collect(),
+        pok,
+    )
+}
+
+// The root of unity of order 8 from the multiplicative generator 5, as in gnark
+const PLONK_GENERATOR: &str =
+    "19540430494807482326159819597004422086093766032135589407132600596362845576832";
+
+/// A Plonk verifying key over a domain of size 8, with two public inputs and a BSB22
+/// commitment.
+pub(crate) fn plonk_vk() -> PlonkVerifyingKey {
+    PlonkVerifyingKey::from_parts(
+        8,
+        fr(8).inverse().expect("non-zero size"),
+        Fr::from_str(PLONK_GENERATOR).expect("valid scalar"),
+        2,
+        fr(5),
+        [g1(fr(101)), g1(fr(102)), g1(fr(103))],
+        g1(fr(104)),
+        g1(fr(105)),
+        g1(fr(106)),
+        g1(fr(107)),
+        g1(fr(108)),
+        vec![g1(fr(109))],
+        vec![3],
+        AffineG1::one(),
+        [AffineG2::one(), g2(fr(12345))],
+    )
+    .expect("valid KZG points")
+}
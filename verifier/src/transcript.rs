@@ -1,108 +1,82 @@
-use alloc::{collections::btree_map::BTreeMap, string::String, vec::Vec};
 use sha2::{Digest, Sha256};
 
-use crate::error::Error;
-
+use crate::{
+    error::{Error, InternalError},
+    hash_to_field::FiatShamirHash,
+};
+
+// A Fiat-Shamir transcript as gnark's, hashing the challenges with H.
+//
+// The challenges are computed in the order of `ids`, each one as
+// H(id || previous challenge || bindings), with no previous challenge for the first one. The
+// bindings are hashed into the running state as they come, so only the challenge which is
+// currently being built can be bound to.
 #[derive(Clone, Debug)]
-pub(crate) struct Challenge {
+pub(crate) struct Transcript<H = Sha256> {
+    h: H,
+    ids: &'static [&'static str],
+    // The index of the challenge being built, ids.len() once all are computed
     position: usize,
-    bindings: Vec<Vec<u8>>,
-    value: Vec<u8>,
-    is_computed: bool,
-}
-
-#[derive(Clone, Debug)]
-pub(crate) struct Transcript {
-    pub(crate) h: Sha256,
-
-    pub(crate) challenges: BTreeMap<String, Challenge>,
-    previous_challenge: Option<Challenge>,
+    previous_challenge: [u8; 32],
 }
 
-impl Transcript {
-    pub(crate) fn new(challenges_id: Option<Vec<String>>) -> Result<Self, Error> {
-        let h = Sha256::new();
+impl<H: FiatShamirHash> Transcript<H> {
+    pub(crate) fn new(ids: &'static [&'static str]) -> Result<Self, Error> {
+        let first = ids.first().ok_or(InternalError::ChallengeNotFound)?;
 
-        if let Some(challenges_id) = challenges_id {
-            let mut challenges = BTreeMap::new();
-            for (position, id) in challenges_id.iter().enumerate() {
-                challenges.insert(
-                    id.clone(),
-                    Challenge {
-                        position,
-                        bindings: Vec::new(),
-                        value: Vec::new(),
-                        is_computed: false,
-                    },
-                );
-            }
+        Ok(Self::resume(ids, H::new_with_prefix(first.as_bytes())))
+    }
 
-            Ok(Transcript {
-                h,
-                challenges,
-                previous_challenge: None,
-            })
-        } else {
-            Ok(Transcript {
-                h,
-                challenges: BTreeMap::new(),
-                previous_challenge: None,
-            })
+    // Starts a transcript from the state returned by `state` for its first challenge, skipping
+    // the id and the bindings that state already holds
+    pub(crate) fn resume(ids: &'static [&'static str], state: H) -> Self {
+        Self {
+            h: state,
+            ids,
+            position: 0,
+            previous_challenge: [0; 32],
         }
     }
 
-    pub(crate) fn bind(&mut self, id: &str, binding: &[u8]) -> Result<(), Error> {
-        let current_challenge = self
-            .challenges
-            .get_mut(id)
-            .ok_or(Error::ChallengeNotFound)?;
-        if current_challenge.is_computed {
-            return Err(Error::ChallengeAlreadyComputed);
-        }
+    // Returns the running state of the challenge being built
+    pub(crate) fn state(&self) -> H {
+        self.h.clone()
+    }
 
-        current_challenge.bindings.push(binding.to_vec());
+    pub(crate) fn bind(&mut self, id: &str, binding: &[u8]) -> Result<(), Error> {
+        self.check_current(id)?;
+        Digest::update(&mut self.h, binding);
 
         Ok(())
     }
 
-    pub(crate) fn compute_challenge(&mut self, challenge_id: &str) -> Result<Vec<u8>, Error> {
-        let challenge = self
-            .challenges
-            .get_mut(challenge_id)
-            .ok_or(Error::ChallengeNotFound)?;
-
-        if challenge.is_computed {
-            return Ok(challenge.value.clone());
+    pub(crate) fn compute_challenge(&mut self, id: &str) -> Result<[u8; 32], Error> {
+        // The last computed challenge can be asked for again
+        if self.position > 0 && self.ids[self.position - 1] == id {
+            return Ok(self.previous_challenge);
         }
+        self.check_current(id)?;
 
-        // Reset the hash function before and after computing the challenge
-        self.h.reset();
-
-        self.h.update(challenge_id.as_bytes());
-
-        if challenge.position != 0 {
-            if let Some(previous_challenge) = &self.previous_challenge {
-                if previous_challenge.position != challenge.position - 1 {
-                    return Err(Error::PreviousChallengeNotComputed);
-                }
-                self.h.update(&previous_challenge.value)
-            } else {
-                return Err(Error::PreviousChallengeNotComputed);
-            }
-        }
+        self.previous_challenge = Digest::finalize_reset(&mut self.h).into();
+        self.position += 1;
 
-        for binding in challenge.bindings.iter() {
-            self.h.update(binding)
+        // Start the next challenge with its id and the one just computed
+        if let Some(next) = self.ids.get(self.position) {
+            Digest::update(&mut self.h, next.as_bytes());
+            Digest::update(&mut self.h, self.previous_challenge);
         }
 
-        let res = self.h.finalize_reset();
-
-        challenge.value = res.to_vec();
-        challenge.is_computed = true;
-
-        // Update the previous challenge reference
-        self.previous_challenge = Some(challenge.clone());
+        Ok(self.previous_challenge)
+    }
 
-        Ok(res.to_vec())
+    fn check_current(&self, id: &str) -> Result<(), Error> {
+        match self.ids.iter().position(|challenge| *challenge == id) {
+            Some(position) if position == self.position => Ok(()),
+            Some(position) if position < self.position => {
+                Err(InternalError::ChallengeAlreadyComputed.into())
+            }
+            Some(_) => Err(InternalError::PreviousChallengeNotComputed.into()),
+            None => Err(InternalError::ChallengeNotFound.into()),
+        }
     }
 }
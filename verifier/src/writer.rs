@@ -0,0 +1,78 @@
+use alloc::vec::Vec;
+use bn::{AffineG1, AffineG2, Fq, Fr};
+
+use crate::{
+    converter::{
+        fq_to_bytes, g1_point_to_compressed_bytes, g1_point_to_uncompressed_bytes,
+        g2_point_to_compressed_bytes, g2_point_to_uncompressed_bytes, PointEncoding,
+    },
+    error::{DecodeError, Error},
+    pairing::LineEvaluationAff,
+};
+
+/// Serializes objects in gnark's format, the counterpart of
+/// [`ByteReader`](crate::reader::ByteReader).
+#[derive(Debug, Default)]
+pub(crate) struct ByteWriter {
+    buffer: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_be_bytes());
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_be_bytes());
+    }
+
+    /// Writes a u32 length prefix, naming `field` in the error if `len` does not fit.
+    pub(crate) fn write_len_u32(&mut self, len: usize, field: &'static str) -> Result<(), Error> {
+        let len = u32::try_from(len).map_err(|_| {
+            Error::malformed(self.buffer.len(), field, DecodeError::InvalidLengthPrefix)
+        })?;
+        self.write_u32(len);
+        Ok(())
+    }
+
+    pub(crate) fn write_fr(&mut self, value: &Fr) {
+        self.write_bytes(&value.into_u256().to_bytes_be());
+    }
+
+    pub(crate) fn write_fq(&mut self, value: &Fq) {
+        self.write_bytes(&fq_to_bytes(*value));
+    }
+
+    pub(crate) fn write_line(&mut self, line: &LineEvaluationAff) {
+        for value in [line.r0, line.r1] {
+            self.write_fq(&value.real());
+            self.write_fq(&value.imaginary());
+        }
+    }
+
+    pub(crate) fn write_g1(&mut self, point: &AffineG1, encoding: PointEncoding) {
+        match encoding {
+            PointEncoding::Compressed => self.write_bytes(&g1_point_to_compressed_bytes(point)),
+            PointEncoding::Uncompressed => self.write_bytes(&g1_point_to_uncompressed_bytes(point)),
+        }
+    }
+
+    pub(crate) fn write_g2(&mut self, point: &AffineG2, encoding: PointEncoding) {
+        match encoding {
+            PointEncoding::Compressed => self.write_bytes(&g2_point_to_compressed_bytes(point)),
+            PointEncoding::Uncompressed => self.write_bytes(&g2_point_to_uncompressed_bytes(point)),
+        }
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+}